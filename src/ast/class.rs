@@ -0,0 +1,112 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::{interpreter::environment::Environment, lexer::tokens::Token, stmt::stmt::Stmt};
+
+use super::{error::RuntimeError, value::Value};
+
+/// A method as written in the class body, it becomes a callable once bound to an instance.
+pub struct Method {
+    pub params: Vec<Token>,
+    pub body: Rc<[Stmt]>,
+}
+
+pub struct Class {
+    pub name: String,
+    pub superclass: Option<Rc<Class>>,
+    pub methods: HashMap<String, Method>,
+    pub closure: Rc<RefCell<Environment>>, // where the class was declared, with `super` defined in subclasses
+}
+
+pub struct Instance {
+    pub class: Rc<Class>,
+    pub fields: HashMap<String, Value>,
+}
+
+impl Class {
+    /// How many arguments constructing the class takes, those of `init` or none without one.
+    pub fn arity(&self) -> usize {
+        self.find_method("init").map_or(0, |(_, init)| init.params.len())
+    }
+
+    // the method `name` of this class or else of the closest superclass that has one, with the
+    // class it was declared in
+    fn find_method(&self, name: &str) -> Option<(&Class, &Method)> {
+        match self.methods.get(name) {
+            Some(method) => Some((self, method)),
+            None => self.superclass.as_ref()?.find_method(name),
+        }
+    }
+
+    /// The method `name` as a callable that sees `instance` as `self`, inherited methods included.
+    pub fn bind(&self, name: &str, instance: &Rc<RefCell<Instance>>) -> Option<Value> {
+        let (owner, method) = self.find_method(name)?;
+
+        // the method runs where its own class was declared, so `super` means that class's superclass
+        let scope = Environment::with_enclosing(owner.closure.clone());
+        scope.borrow_mut().define("self", Value::Instance(instance.clone()));
+
+        Some(Value::function(name, &method.params, &method.body, &scope))
+    }
+
+    /// A new instance of `class`, `init` runs on it with `arguments` before it is handed out.
+    /// The arity is checked by the caller.
+    pub fn instantiate(class: &Rc<Class>, arguments: &Vec<Value>) -> Result<Value, RuntimeError> {
+        let instance = Rc::new(RefCell::new(Instance { class: class.clone(), fields: HashMap::new() }));
+
+        if let Some(Value::Callable { fun, .. }) = class.bind("init", &instance) {
+            fun(arguments)?;
+        }
+
+        Ok(Value::Instance(instance))
+    }
+}
+
+impl Instance {
+    /// The field `name` or else the method `name` bound to `instance`, fields shadow methods.
+    pub fn get(instance: &Rc<RefCell<Instance>>, name: &str) -> Option<Value> {
+        let this = instance.borrow();
+        this.fields.get(name).cloned().or_else(|| this.class.bind(name, instance))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::tokens::TokenKind;
+
+    fn class(methods: HashMap<String, Method>) -> Rc<Class> {
+        Rc::new(Class { name: "Point".to_string(), superclass: None, methods, closure: Environment::new() })
+    }
+
+    #[test]
+    fn test_arity_comes_from_init() {
+        assert_eq!(class(HashMap::new()).arity(), 0);
+
+        let x = Token::dynamic_tokenkind("filename".to_string(), TokenKind::Identifier, "x".to_string(), 1, 1).unwrap();
+        let init = Method { params: vec![x], body: Rc::new([]) };
+        assert_eq!(class(HashMap::from([("init".to_string(), init)])).arity(), 1);
+    }
+
+    #[test]
+    fn test_fields_shadow_methods() {
+        let method = Method { params: vec![], body: Rc::new([]) };
+        let point = class(HashMap::from([("len".to_string(), method)]));
+
+        let Value::Instance(instance) = Class::instantiate(&point, &vec![]).unwrap() else { panic!("expected an instance") };
+        assert!(matches!(Instance::get(&instance, "len"), Some(Value::Callable { .. })));
+        assert_eq!(Instance::get(&instance, "x"), None);
+
+        instance.borrow_mut().fields.insert("len".to_string(), Value::Number(2.0));
+        assert_eq!(Instance::get(&instance, "len"), Some(Value::Number(2.0)));
+    }
+
+    #[test]
+    fn test_methods_are_inherited() {
+        let method = Method { params: vec![], body: Rc::new([]) };
+        let base = class(HashMap::from([("len".to_string(), method)]));
+        let derived = Class { name: "Derived".to_string(), superclass: Some(base), methods: HashMap::new(), closure: Environment::new() };
+
+        assert!(derived.find_method("len").is_some_and(|(owner, _)| owner.name == "Point"));
+        assert!(derived.find_method("missing").is_none());
+    }
+}
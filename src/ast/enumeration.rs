@@ -0,0 +1,88 @@
+use std::rc::Rc;
+
+use super::value::Value;
+
+/// A variant as declared, `Rect(w, h)` has the fields `w` and `h`, `Empty` has none.
+pub struct Variant {
+    pub name: String,
+    pub fields: Vec<String>,
+}
+
+pub struct Enum {
+    pub name: String,
+    pub variants: Vec<Variant>,
+}
+
+impl Enum {
+    /// `Shape.Empty` is the value itself, a variant with fields gives the callable that builds it.
+    pub fn variant(enumeration: &Rc<Enum>, name: &str) -> Option<Value> {
+        let variant = enumeration.variants.iter().find(|variant| variant.name == name)?;
+
+        if variant.fields.is_empty() {
+            return Some(Value::Variant { enumeration: enumeration.clone(), variant: name.to_string(), values: vec![] });
+        }
+
+        let owner = enumeration.clone();
+        let variant_name = name.to_string();
+        Some(Value::Callable {
+            name: format!("{}.{}", enumeration.name, name),
+            arity: variant.fields.len(),
+            fun: Rc::new(move |arguments| {
+                Ok(Value::Variant { enumeration: owner.clone(), variant: variant_name.clone(), values: arguments.clone() })
+            }),
+        })
+    }
+
+    /// The payload of `variant` named `field`, `r` of `Circle(r)` for example.
+    pub fn field<'a>(&self, variant: &str, values: &'a [Value], field: &str) -> Option<&'a Value> {
+        let variant = self.variants.iter().find(|candidate| candidate.name == variant)?;
+        let position = variant.fields.iter().position(|candidate| candidate == field)?;
+        values.get(position)
+    }
+
+    /// The variant names, for error messages.
+    pub fn variant_names(&self) -> Vec<&str> {
+        self.variants.iter().map(|variant| variant.name.as_str()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shape() -> Rc<Enum> {
+        Rc::new(Enum {
+            name: "Shape".to_string(),
+            variants: vec![
+                Variant { name: "Circle".to_string(), fields: vec!["r".to_string()] },
+                Variant { name: "Empty".to_string(), fields: vec![] },
+            ],
+        })
+    }
+
+    #[test]
+    fn test_variants() {
+        let shape = shape();
+
+        assert_eq!(Enum::variant(&shape, "Empty").unwrap().to_string(), "Shape.Empty");
+        assert!(Enum::variant(&shape, "Square").is_none());
+
+        let Some(Value::Callable { name, arity, fun }) = Enum::variant(&shape, "Circle") else { panic!("expected a constructor") };
+        assert_eq!((name.as_str(), arity), ("Shape.Circle", 1));
+
+        let circle = fun(&vec![Value::Number(2.0)]).unwrap();
+        assert_eq!(circle.to_string(), "Shape.Circle(2)");
+        assert_eq!(circle, fun(&vec![Value::Number(2.0)]).unwrap());
+        assert_ne!(circle, fun(&vec![Value::Number(3.0)]).unwrap());
+    }
+
+    #[test]
+    fn test_field() {
+        let shape = shape();
+        let values = [Value::Number(2.0)];
+
+        assert_eq!(shape.field("Circle", &values, "r"), Some(&Value::Number(2.0)));
+        assert_eq!(shape.field("Circle", &values, "w"), None);
+        assert_eq!(shape.variant_names(), vec!["Circle", "Empty"]);
+    }
+}
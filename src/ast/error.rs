@@ -0,0 +1,105 @@
+use crate::{diagnostics::error::{Error, ErrorKind}, lexer::tokens::Span};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuntimeErrorKind {
+    UnsupportedUnary {
+        operator: String,
+        operand: &'static str, // type name of the operand
+    },
+    UnsupportedBinary {
+        operator: String,
+        left: &'static str,
+        right: &'static str,
+    },
+    DivisionByZero,
+    UndefinedVariable(String),
+    UndefinedProperty(String),
+    NoProperties(&'static str), // type name of the value that was accessed
+    NotIndexable(&'static str),
+    InvalidKey(&'static str),   // type name of the key that isnt a string
+    InvalidIndex(String),       // the index as written in the message
+    IndexOutOfBounds {
+        index: i64,
+        length: usize,
+    },
+    InvalidSuperclass(&'static str), // type name of what a class tried to inherit from
+    NoMatch(String),                 // the value no arm matched, as shown in the message
+    NotCallable(&'static str),
+    ArityMismatch {
+        name: String,
+        expected: usize,
+        found: usize,
+    },
+    StackOverflow(usize), // how deep calls may nest
+    InvalidArgument {
+        name: String,             // the native function
+        position: usize,          // counted from 1
+        expected: &'static str,   // what it takes, like "an array"
+        found: &'static str,
+    },
+}
+
+pub type RuntimeError = Error<RuntimeErrorKind>;
+
+impl RuntimeError {
+    /// An error raised inside a callable, which doesnt know where it was called from.
+    /// `Expr::Call` puts it at the call.
+    pub fn unlocated(kind: RuntimeErrorKind) -> Self {
+        Self::new(kind, Span::default(), 0, 0)
+    }
+}
+
+impl ErrorKind for RuntimeErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            RuntimeErrorKind::UnsupportedUnary { .. } => "R0001",
+            RuntimeErrorKind::UnsupportedBinary { .. } => "R0002",
+            RuntimeErrorKind::DivisionByZero => "R0003",
+            RuntimeErrorKind::UndefinedVariable(_) => "R0004",
+            RuntimeErrorKind::UndefinedProperty(_) => "R0005",
+            RuntimeErrorKind::NoProperties(_) => "R0006",
+            RuntimeErrorKind::NotIndexable(_) => "R0007",
+            RuntimeErrorKind::InvalidIndex(_) => "R0008",
+            RuntimeErrorKind::IndexOutOfBounds { .. } => "R0009",
+            RuntimeErrorKind::InvalidKey(_) => "R0010",
+            RuntimeErrorKind::InvalidSuperclass(_) => "R0011",
+            RuntimeErrorKind::NoMatch(_) => "R0012",
+            RuntimeErrorKind::NotCallable(_) => "R0100",
+            RuntimeErrorKind::ArityMismatch { .. } => "R0101",
+            RuntimeErrorKind::InvalidArgument { .. } => "R0102",
+            RuntimeErrorKind::StackOverflow(_) => "R0103",
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            RuntimeErrorKind::UnsupportedUnary { operator, operand } => {
+                format!("Unsupported unary operator '{}' for {}", operator, operand)
+            },
+            RuntimeErrorKind::UnsupportedBinary { operator, left, right } => {
+                format!("Binary operator '{}' is not supported between {} and {}", operator, left, right)
+            },
+            RuntimeErrorKind::DivisionByZero => "Division by zero".to_string(),
+            RuntimeErrorKind::UndefinedVariable(name) => format!("Undefined variable '{}'", name),
+            RuntimeErrorKind::UndefinedProperty(name) => format!("Undefined property '{}'", name),
+            RuntimeErrorKind::NoProperties(type_name) => format!("{} has no properties", type_name),
+            RuntimeErrorKind::NotIndexable(type_name) => format!("{} cannot be indexed", type_name),
+            RuntimeErrorKind::InvalidIndex(index) => format!("Index must be a whole number, found {}", index),
+            RuntimeErrorKind::InvalidKey(type_name) => format!("Object keys must be strings, found {}", type_name),
+            RuntimeErrorKind::InvalidSuperclass(type_name) => format!("Superclass must be a class, found {}", type_name),
+            RuntimeErrorKind::NoMatch(value) => format!("No match arm matches {}", value),
+            RuntimeErrorKind::IndexOutOfBounds { index, length } => {
+                format!("Index {} is out of bounds for length {}", index, length)
+            },
+            RuntimeErrorKind::NotCallable(type_name) => format!("{} is not callable", type_name),
+            RuntimeErrorKind::ArityMismatch { name, expected, found } => {
+                format!("Callable {} expected {} arguments but got {}", name, expected, found)
+            },
+            RuntimeErrorKind::StackOverflow(limit) => format!("Stack overflow, calls nested more than {} deep", limit),
+            RuntimeErrorKind::InvalidArgument { name, position, expected, found } => {
+                format!("Argument {} of {} must be {}, found {}", position, name, expected, found)
+            },
+        }
+    }
+}
+
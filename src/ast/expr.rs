@@ -1,75 +1,479 @@
-use crate::lexer::tokens::Token;
+use crate::lexer::tokens::{Span, Token, TokenKind};
 
-use super::value::Value;
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::{interpreter::environment::Environment, stmt::stmt::Stmt};
+
+use super::{class::{Class, Instance}, enumeration::Enum, pattern::Arm, error::{RuntimeError, RuntimeErrorKind}, value::Value};
+
+/// The key of a property in an object literal.
+pub enum Key {
+    Name(String),   // `name: value`, `"quoted name": value` and the shorthand `name`
+    Computed {      // `[expr]: value`
+        bracket: Box<Token>,
+        key: Box<Expr>
+    },
+}
+
+// the target of an assignment once its indices are evaluated, see `Expr::place`
+struct Place<'a> {
+    root: Root<'a>,
+    path: Vec<Step<'a>>, // from the root inwards
+}
+
+enum Root<'a> {
+    Variable(&'a Token), // a variable or `self`, read and written back
+    Value(Value),        // a temporary, only shared instances inside it keep the change
+}
+
+enum Step<'a> {
+    Index {
+        position: Value,
+        bracket: &'a Token,
+        span: Span, // of the index expression, for errors
+    },
+    Field(&'a Token),
+}
 
 pub enum Expr {
     Literal {
         value: Value,
+        span: Span
     },
     Grouping {
-        expression: Box<Expr>
+        expression: Box<Expr>,
+        span: Span
     },
     Binary {
         left: Box<Expr>,
         operator: Token,
-        right: Box<Expr>
+        right: Box<Expr>,
+        span: Span
     },
     Unary {
         operator: Token,
-        right: Box<Expr>
+        right: Box<Expr>,
+        span: Span
+    },
+    Conditional {
+        condition: Box<Expr>,
+        then_branch: Box<Expr>,
+        else_branch: Box<Expr>,
+        span: Span
     },
     Call {
         callee: Box<Expr>,
         paren: Token,
-        arguments: Vec<Expr>
+        arguments: Vec<Expr>,
+        optional: bool, // `f?.()` gives null instead of calling a null `f`
+        span: Span
+    },
+    Get {
+        object: Box<Expr>,
+        name: Token,
+        optional: bool, // `a?.b` gives null when `a` is null or has no `b`
+        span: Span
+    },
+    Interpolation {
+        parts: Vec<Expr>, // string literals and interpolated expressions, in source order
+        span: Span
+    },
+    Variable {
+        name: Token,
+        span: Span
+    },
+    This {
+        keyword: Token, // `self`, looked up like a variable the bound method defines
+        span: Span
+    },
+    Super {
+        keyword: Token, // `super`, defined like `self` around the methods of a subclass
+        method: Box<Token>,
+        span: Span
+    },
+    Array {
+        elements: Vec<Expr>,
+        span: Span
+    },
+    Object {
+        properties: Vec<(Key, Expr)>,
+        span: Span
+    },
+    Index {
+        object: Box<Expr>,
+        bracket: Token,
+        index: Box<Expr>,
+        span: Span
+    },
+    Slice {
+        object: Box<Expr>,
+        bracket: Token,
+        start: Option<Box<Expr>>, // `a[:2]` has no start
+        end: Option<Box<Expr>>,
+        span: Span
+    },
+    Assign {
+        target: Box<Expr>, // a variable or an index
+        operator: Token,   // `=` or a compound one like `+=`
+        value: Box<Expr>,
+        span: Span
+    },
+    Function {
+        params: Vec<Token>,
+        body: Rc<[Stmt]>, // an arrow's expression body is a single return
+        span: Span
+    },
+    Match {
+        keyword: Token,
+        value: Box<Expr>,
+        arms: Vec<Arm>, // tried in order, the first one that matches runs
+        span: Span
     }
 }
 
 impl Expr {
 
-    pub fn evaluate(&self) -> Result<Value, String> {
+    /// The part of the source the whole expression was parsed from.
+    pub fn span(&self) -> Span {
+        match self {
+            Expr::Literal { span, .. }
+            | Expr::Grouping { span, .. }
+            | Expr::Binary { span, .. }
+            | Expr::Unary { span, .. }
+            | Expr::Conditional { span, .. }
+            | Expr::Call { span, .. }
+            | Expr::Get { span, .. }
+            | Expr::Interpolation { span, .. }
+            | Expr::Variable { span, .. }
+            | Expr::This { span, .. }
+            | Expr::Super { span, .. }
+            | Expr::Array { span, .. }
+            | Expr::Object { span, .. }
+            | Expr::Index { span, .. }
+            | Expr::Slice { span, .. }
+            | Expr::Assign { span, .. }
+            | Expr::Function { span, .. }
+            | Expr::Match { span, .. } => *span,
+        }
+    }
+
+    // resolves where an assignment to `target` writes, evaluating every index in it left to right.
+    // Nothing is read yet, so the value can be evaluated next and still see its own changes kept
+    fn place<'a>(target: &'a Expr, environment: &Rc<RefCell<Environment>>) -> Result<Place<'a>, RuntimeError> {
+        match target {
+            Expr::Variable { name, .. } | Expr::This { keyword: name, .. } => Ok(Place { root: Root::Variable(name), path: vec![] }),
+            Expr::Index { object, bracket, index, .. } => {
+                let mut place = Self::place(object, environment)?;
+                place.path.push(Step::Index { position: index.evaluate(environment)?, bracket, span: index.span() });
+                Ok(place)
+            },
+            Expr::Get { object, name, .. } => {
+                let mut place = Self::place(object, environment)?;
+                place.path.push(Step::Field(name));
+                Ok(place)
+            },
+            // a temporary like `make().x`, the change only sticks to a shared instance inside it
+            other => Ok(Place { root: Root::Value(other.evaluate(environment)?), path: vec![] }),
+        }
+    }
+
+    // replaces the value at `place` with what `change` makes of it and writes the result back, so
+    // `a[0][1] = x` rebuilds `a[0]` and then stores it in `a`. The variable is read only now
+    fn update(
+        place: &Place,
+        environment: &Rc<RefCell<Environment>>,
+        change: &mut dyn FnMut(Value) -> Result<Value, RuntimeError>
+    ) -> Result<(), RuntimeError> {
+        match &place.root {
+            Root::Variable(name) => {
+                let current = environment.borrow().get(name)?;
+                let value = Self::write(current, &place.path, change)?;
+                environment.borrow_mut().assign(name, value)
+            },
+            Root::Value(value) => Self::write(value.clone(), &place.path, change).map(|_| ()),
+        }
+    }
+
+    // `container` with the value at the end of `path` changed
+    fn write(
+        mut container: Value,
+        path: &[Step],
+        change: &mut dyn FnMut(Value) -> Result<Value, RuntimeError>
+    ) -> Result<Value, RuntimeError> {
+        let Some((step, rest)) = path.split_first() else {
+            return change(container);
+        };
+
+        match step {
+            Step::Index { position, bracket, span } => {
+                // a new field starts out as null
+                let current = match (&container, position) {
+                    (Value::Object(fields), Value::String(key)) => fields.get(key).cloned().unwrap_or(Value::Null),
+                    _ => container.index(position, bracket, *span)?,
+                };
+                let value = Self::write(current, rest, change)?;
+                container.set_index(position, value, bracket, *span)?;
+            },
+            Step::Field(name) => match &mut container {
+                Value::Object(fields) => {
+                    let current = fields.get(&name.lexeme).cloned().unwrap_or(Value::Null);
+                    fields.insert(name.lexeme.clone(), Self::write(current, rest, change)?);
+                },
+                // the instance is shared, so this changes it for every copy. It isnt borrowed
+                // while `change` runs as that may read the instance again
+                Value::Instance(instance) => {
+                    let current = Instance::get(instance, &name.lexeme).unwrap_or(Value::Null);
+                    let value = Self::write(current, rest, change)?;
+                    instance.borrow_mut().fields.insert(name.lexeme.clone(), value);
+                },
+                _ => return Err(RuntimeError::at(RuntimeErrorKind::NoProperties(container.type_name()), name)),
+            },
+        }
+        Ok(container)
+    }
+
+    // the binary operator a compound assignment applies, `None` for plain `=`
+    fn compound_operator(kind: &TokenKind) -> Option<TokenKind> {
+        match kind {
+            TokenKind::PlusEqual => Some(TokenKind::Plus),
+            TokenKind::MinusEqual => Some(TokenKind::Minus),
+            TokenKind::StarEqual => Some(TokenKind::Star),
+            TokenKind::SlashEqual => Some(TokenKind::Slash),
+            TokenKind::PercentEqual => Some(TokenKind::Percent),
+            _ => None
+        }
+    }
+
+    // evaluates a call, property, index or slice. Its object is evaluated as a link too, so once an
+    // optional link finds null the rest of the chain is skipped, `None`, and `a?.b.c` is null
+    // instead of failing on `.c`. Parentheses end a chain
+    fn link(&self, environment: &Rc<RefCell<Environment>>) -> Result<Option<Value>, RuntimeError> {
+        let (object, optional) = match self {
+            Expr::Call { callee, optional, .. } => (callee, *optional),
+            Expr::Get { object, optional, .. } => (object, *optional),
+            Expr::Index { object, .. } | Expr::Slice { object, .. } => (object, false),
+            _ => return self.evaluate(environment).map(Some),
+        };
+
+        let object = match object.link(environment)? {
+            None => return Ok(None),
+            Some(Value::Null) if optional => return Ok(None),
+            Some(object) => object,
+        };
+
+        match self {
+            Expr::Call { callee, paren, arguments, span, .. } => {
+                let (name, arity) = match &object {
+                    Value::Callable { name, arity, .. } => (name.clone(), *arity),
+                    // calling a class constructs an instance of it
+                    Value::Class(class) => (class.name.clone(), class.arity()),
+                    other => return Err(RuntimeError::new(
+                        RuntimeErrorKind::NotCallable(other.type_name()),
+                        callee.span(),
+                        paren.line_number,
+                        paren.column_number
+                    ))
+                };
+
+                if arguments.len() != arity {
+                    return Err(RuntimeError::new(RuntimeErrorKind::ArityMismatch {
+                        name,
+                        expected: arity,
+                        found: arguments.len(),
+                    }, *span, paren.line_number, paren.column_number));
+                }
+
+                let mut arguments_values = vec![];
+
+                for arg in arguments {
+                    let val = arg.evaluate(environment)?;
+                    arguments_values.push(val);
+                }
+
+                let result = match object {
+                    Value::Class(class) => Class::instantiate(&class, &arguments_values),
+                    Value::Callable { fun, .. } => fun(&arguments_values),
+                    _ => unreachable!("only callables and classes get past the check above"),
+                };
+
+                // a callable doesnt know where it was called from, so errors it raises without a place go here
+                result.map(Some).map_err(|error| match error.line_number {
+                    0 => error.located_at(*span, paren.line_number, paren.column_number),
+                    _ => error,
+                })
+            },
+            Expr::Get { name, optional, .. } => {
+                let value = match object {
+                    Value::Object(fields) => match fields.get(&name.lexeme) {
+                        Some(value) => Ok(value.clone()),
+                        None if *optional => Ok(Value::Null),
+                        None => Err(RuntimeError::at(RuntimeErrorKind::UndefinedProperty(name.lexeme.clone()), name)
+                            .with_help(&format!("use `?.{}` if it may be missing", name.lexeme))),
+                    },
+                    Value::Instance(instance) => match Instance::get(&instance, &name.lexeme) {
+                        Some(value) => Ok(value),
+                        None if *optional => Ok(Value::Null),
+                        None => Err(RuntimeError::at(RuntimeErrorKind::UndefinedProperty(name.lexeme.clone()), name)
+                            .with_help(&format!("use `?.{}` if it may be missing", name.lexeme))),
+                    },
+                    Value::Enum(enumeration) => match Enum::variant(&enumeration, &name.lexeme) {
+                        Some(value) => Ok(value),
+                        None if *optional => Ok(Value::Null),
+                        None => Err(RuntimeError::at(RuntimeErrorKind::UndefinedProperty(name.lexeme.clone()), name)
+                            .with_help(&format!("{} has the variants {}", enumeration.name, enumeration.variant_names().join(", ")))),
+                    },
+                    // the payload of a variant is read by the field names it was declared with
+                    Value::Variant { enumeration, variant, values } => match enumeration.field(&variant, &values, &name.lexeme) {
+                        Some(value) => Ok(value.clone()),
+                        None if *optional => Ok(Value::Null),
+                        None => Err(RuntimeError::at(RuntimeErrorKind::UndefinedProperty(name.lexeme.clone()), name)),
+                    },
+                    other => Err(RuntimeError::at(RuntimeErrorKind::NoProperties(other.type_name()), name)),
+                };
+                value.map(Some)
+            },
+            Expr::Index { bracket, index, .. } => {
+                object.index(&index.evaluate(environment)?, bracket, index.span()).map(Some)
+            },
+            Expr::Slice { bracket, start, end, span, .. } => {
+                let start = start.as_ref().map(|start| start.evaluate(environment)).transpose()?;
+                let end = end.as_ref().map(|end| end.evaluate(environment)).transpose()?;
+                object.slice(start.as_ref(), end.as_ref(), bracket, *span).map(Some)
+            },
+            _ => unreachable!("only links get past the first match"),
+        }
+    }
+
+    pub fn evaluate(&self, environment: &Rc<RefCell<Environment>>) -> Result<Value, RuntimeError> {
         match self {
-            Expr::Literal { value } => Ok((*value).clone()),
-            Expr::Grouping { expression } => expression.evaluate(),
-            Expr::Binary { left, operator, right } => {
-                let left_value = left.evaluate()?;
-                let right_value = right.evaluate()?;
+            Expr::Literal { value, .. } => Ok((*value).clone()),
+            Expr::Grouping { expression, .. } => expression.evaluate(environment),
+            Expr::Binary { left, operator, right, .. } => {
+                let left_value = left.evaluate(environment)?;
+
+                // `and` and `or` give the operand that decides the result, like `0 or "default"` giving
+                // "default", and dont evaluate the right side when the left one already decides
+                match (&operator.kind, &left_value) {
+                    (TokenKind::And, _) if !left_value.is_truthy() => return Ok(left_value),
+                    (TokenKind::Or, _) if left_value.is_truthy() => return Ok(left_value),
+                    (TokenKind::And | TokenKind::Or, _) => return right.evaluate(environment),
+                    (TokenKind::QuestionQuestion, Value::Null) => return right.evaluate(environment),
+                    (TokenKind::QuestionQuestion, _) => return Ok(left_value),
+                    _ => ()
+                }
+
+                let right_value = right.evaluate(environment)?;
                 left_value.apply_binary_op(operator, &right_value)
             },
-            Expr::Unary { operator, right } => {
-                let right_value = right.evaluate()?;
+            Expr::Unary { operator, right, .. } => {
+                let right_value = right.evaluate(environment)?;
                 right_value.apply_unary_op(operator)
             },
-            Expr::Call { callee, paren, arguments } => {
-                let callable = (*callee).evaluate()?;
+            Expr::Conditional { condition, then_branch, else_branch, .. } => {
+                // only the chosen branch is evaluated
+                if condition.evaluate(environment)?.is_truthy() {
+                    then_branch.evaluate(environment)
+                } else {
+                    else_branch.evaluate(environment)
+                }
+            },
+            // the links of a chain like `a?.b.c()`
+            Expr::Call { .. } | Expr::Get { .. } | Expr::Index { .. } | Expr::Slice { .. } => {
+                Ok(self.link(environment)?.unwrap_or(Value::Null))
+            },
+            Expr::Variable { name, .. } => environment.borrow().get(name),
+            Expr::This { keyword, .. } => environment.borrow().get(keyword),
+            Expr::Super { keyword, method, .. } => {
+                let superclass = environment.borrow().get(keyword)?;
+                let this = Token { lexeme: "self".to_string(), ..keyword.clone() };
 
-                match callable {
-                    Value::Callable { 
-                        name,
-                        arity,
-                        fun 
-                    } => {
-
-                        if arguments.len() != arity {
-                            return Err(format!(
-                                "Callable {} expected {} arguments but got {}",
-                                name,
-                                arity,
-                                arguments.len()
-                            ));
-                        }
+                // the superclass's method, but still bound to the instance the current method runs on
+                match (superclass, environment.borrow().get(&this)?) {
+                    (Value::Class(superclass), Value::Instance(instance)) => {
+                        superclass.bind(&method.lexeme, &instance).ok_or_else(|| {
+                            RuntimeError::at(RuntimeErrorKind::UndefinedProperty(method.lexeme.clone()), method)
+                                .with_help(&format!("no superclass of this class has a method `{}`", method.lexeme))
+                        })
+                    },
+                    _ => unreachable!("the parser only allows `super` in the methods of a subclass"),
+                }
+            },
+            Expr::Array { elements, .. } => {
+                let mut values = vec![];
+
+                for element in elements {
+                    values.push(element.evaluate(environment)?);
+                }
+
+                Ok(Value::Array(values))
+            },
+            Expr::Object { properties, .. } => {
+                let mut fields = HashMap::new();
+
+                for (key, value) in properties {
+                    let key = match key {
+                        Key::Name(name) => name.clone(),
+                        Key::Computed { bracket, key } => Value::key(&key.evaluate(environment)?, bracket, key.span())?,
+                    };
+                    fields.insert(key, value.evaluate(environment)?);
+                }
 
-                        let mut arguments_values = vec![];
+                Ok(Value::Object(fields))
+            },
+            Expr::Assign { target, operator, value, .. } => {
+                // `a[i()] += f()` runs `i()` before `f()`, and `a` is read after both
+                let place = Self::place(target, environment)?;
+                let value = value.evaluate(environment)?;
+                let compound = Self::compound_operator(&operator.kind);
+                let mut assigned = Value::Null;
+
+                Self::update(&place, environment, &mut |current| {
+                    assigned = match &compound {
+                        // keeps the `+=` lexeme so errors show what was written
+                        Some(kind) => current.apply_binary_op(&Token { kind: kind.clone(), ..operator.clone() }, &value)?,
+                        None => value.clone(),
+                    };
+                    Ok(assigned.clone())
+                })?;
+
+                Ok(assigned)
+            },
+            Expr::Function { params, body, .. } => {
+                Ok(Value::function("anonymous", params, body, environment))
+            },
+            Expr::Match { keyword, value, arms, .. } => {
+                let value = value.evaluate(environment)?;
 
-                        for arg in arguments {
-                            let val = arg.evaluate()?;
-                            arguments_values.push(val);
+                for arm in arms {
+                    // the names an arm binds only exist in its guard and body
+                    let scope = Environment::with_enclosing(environment.clone());
+
+                    if !arm.pattern.matches(&value, &scope)? {
+                        continue;
+                    }
+                    if let Some(guard) = &arm.guard {
+                        if !guard.evaluate(&scope)?.is_truthy() {
+                            continue;
                         }
+                    }
+                    return arm.body.evaluate(&scope);
+                }
 
-                        Ok(fun(&arguments_values))
-                    },
-                    other => Err("is not callable".to_string())
+                let shown = match &value {
+                    Value::String(s) => format!("\"{}\"", s),
+                    other => other.to_string(),
+                };
+                Err(RuntimeError::at(RuntimeErrorKind::NoMatch(shown), keyword)
+                    .with_help("add a `_ => ...` arm to handle every other value"))
+            },
+            Expr::Interpolation { parts, .. } => {
+                let mut result = String::new();
+
+                for part in parts {
+                    result.push_str(&part.evaluate(environment)?.to_string());
                 }
+
+                Ok(Value::String(result))
             },
         }
     }
@@ -79,14 +483,14 @@ impl Expr {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::lexer::tokens::{Token, TokenKind};
 
     #[test]
     fn test_literal_evaluation() {
         let expr = Expr::Literal {
             value: Value::Number(42.0),
+            span: Span::default(),
         };
-        let result = expr.evaluate();
+        let result = expr.evaluate(&Environment::new());
         assert_eq!(result, Ok(Value::Number(42.0)));
     }
 
@@ -95,9 +499,11 @@ mod tests {
         let expr = Expr::Grouping {
             expression: Box::new(Expr::Literal {
                 value: Value::Number(10.0),
+                span: Span::default(),
             }),
+            span: Span::default(),
         };
-        let result = expr.evaluate();
+        let result = expr.evaluate(&Environment::new());
         assert_eq!(result, Ok(Value::Number(10.0)));
     }
 
@@ -106,6 +512,7 @@ mod tests {
         let expr = Expr::Binary {
             left: Box::new(Expr::Literal {
                 value: Value::Number(5.0),
+                span: Span::default(),
             }),
             operator: Token::static_tokenkind(
                 "filename".to_string(),
@@ -114,9 +521,11 @@ mod tests {
                 1).unwrap(),
             right: Box::new(Expr::Literal {
                 value: Value::Number(3.0),
+                span: Span::default(),
             }),
+            span: Span::default(),
         };
-        let result = expr.evaluate();
+        let result = expr.evaluate(&Environment::new());
         assert_eq!(result, Ok(Value::Number(8.0)));
     }
 
@@ -130,9 +539,11 @@ mod tests {
                 1).unwrap(),
             right: Box::new(Expr::Literal {
                 value: Value::Number(5.0),
+                span: Span::default(),
             }),
+            span: Span::default(),
         };
-        let result = expr.evaluate();
+        let result = expr.evaluate(&Environment::new());
         assert_eq!(result, Ok(Value::Number(-5.0)));
     }
 
@@ -141,6 +552,7 @@ mod tests {
         let expr = Expr::Binary {
             left: Box::new(Expr::Literal {
                 value: Value::Number(10.0),
+                span: Span::default(),
             }),
             operator: Token::static_tokenkind(
                 "filename".to_string(),
@@ -149,17 +561,128 @@ mod tests {
                 1).unwrap(),
             right: Box::new(Expr::Literal {
                 value: Value::Number(5.0),
+                span: Span::default(),
             }),
+            span: Span::default(),
         };
-        let result = expr.evaluate();
+        let result = expr.evaluate(&Environment::new());
         assert_eq!(result, Ok(Value::Boolean(true)));
     }
 
+    #[test]
+    fn test_get_property() {
+        let name = |lexeme: &str| Token::dynamic_tokenkind("filename".to_string(), TokenKind::Identifier, lexeme.to_string(), 1, 1).unwrap();
+        let object = || Box::new(Expr::Literal {
+            value: Value::Object(std::collections::HashMap::from([("x".to_string(), Value::Number(1.0))])),
+            span: Span::default(),
+        });
+
+        let get = Expr::Get { object: object(), name: name("x"), optional: false, span: Span::default() };
+        assert_eq!(get.evaluate(&Environment::new()), Ok(Value::Number(1.0)));
+
+        let missing = Expr::Get { object: object(), name: name("y"), optional: false, span: Span::default() };
+        assert_eq!(missing.evaluate(&Environment::new()).unwrap_err().kind, RuntimeErrorKind::UndefinedProperty("y".to_string()));
+
+        let optional = Expr::Get { object: object(), name: name("y"), optional: true, span: Span::default() };
+        assert_eq!(optional.evaluate(&Environment::new()), Ok(Value::Null));
+    }
+
+    #[test]
+    fn test_not_callable() {
+        let expr = Expr::Call {
+            callee: Box::new(Expr::Literal {
+                value: Value::Number(1.0),
+                span: Span::new(0, 1),
+            }),
+            paren: Token::static_tokenkind(
+                "filename".to_string(),
+                TokenKind::RightParen,
+                1,
+                4).unwrap(),
+            arguments: vec![],
+            optional: false,
+            span: Span::new(0, 3),
+        };
+        let result = expr.evaluate(&Environment::new()).unwrap_err();
+        assert_eq!(result.kind, RuntimeErrorKind::NotCallable("Number"));
+        assert_eq!(result.span, Span::new(0, 1));
+        assert_eq!(result.code(), "R0100");
+    }
+
+    #[test]
+    fn test_unsupported_binary_message() {
+        let expr = Expr::Binary {
+            left: Box::new(Expr::Literal {
+                value: Value::Number(1.0),
+                span: Span::default(),
+            }),
+            operator: Token::static_tokenkind(
+                "filename".to_string(),
+                TokenKind::Minus,
+                1,
+                3).unwrap(),
+            right: Box::new(Expr::Literal {
+                value: Value::String("a".to_string()),
+                span: Span::default(),
+            }),
+            span: Span::default(),
+        };
+        let result = expr.evaluate(&Environment::new()).unwrap_err();
+        assert_eq!(result.to_string(), "Binary operator '-' is not supported between Number and String in line 1 column 3");
+    }
+
+    #[test]
+    fn test_and_short_circuits() {
+        // the right side would fail, so it must never be evaluated
+        let expr = Expr::Binary {
+            left: Box::new(Expr::Literal {
+                value: Value::Boolean(false),
+                span: Span::default(),
+            }),
+            operator: Token::static_tokenkind(
+                "filename".to_string(),
+                TokenKind::And,
+                1,
+                1).unwrap(),
+            right: Box::new(Expr::Unary {
+                operator: Token::static_tokenkind(
+                    "filename".to_string(),
+                    TokenKind::Minus,
+                    1,
+                    1).unwrap(),
+                right: Box::new(Expr::Literal {
+                    value: Value::Boolean(true),
+                    span: Span::default(),
+                }),
+                span: Span::default(),
+            }),
+            span: Span::default(),
+        };
+        let result = expr.evaluate(&Environment::new());
+        assert_eq!(result, Ok(Value::Boolean(false)));
+    }
+
+    #[test]
+    fn test_interpolation_evaluation() {
+        let expr = Expr::Interpolation {
+            parts: vec![
+                Expr::Literal { value: Value::String("total: ".to_string()), span: Span::default() },
+                Expr::Literal { value: Value::Number(3.0), span: Span::default() },
+                Expr::Literal { value: Value::String(", ok: ".to_string()), span: Span::default() },
+                Expr::Literal { value: Value::Boolean(true), span: Span::default() },
+            ],
+            span: Span::default(),
+        };
+        let result = expr.evaluate(&Environment::new());
+        assert_eq!(result, Ok(Value::String("total: 3, ok: true".to_string())));
+    }
+
     #[test]
     fn test_binary_evaluation_division_by_zero() {
         let expr = Expr::Binary {
             left: Box::new(Expr::Literal {
                 value: Value::Number(10.0),
+                span: Span::default(),
             }),
             operator: Token::static_tokenkind(
                 "filename".to_string(),
@@ -168,9 +691,11 @@ mod tests {
                 1).unwrap(),
             right: Box::new(Expr::Literal {
                 value: Value::Number(0.0),
+                span: Span::default(),
             }),
+            span: Span::default(),
         };
-        let result = expr.evaluate();
-        assert!(result.is_err());
+        let result = expr.evaluate(&Environment::new());
+        assert_eq!(result.unwrap_err().kind, RuntimeErrorKind::DivisionByZero);
     }
 }
\ No newline at end of file
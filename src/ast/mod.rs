@@ -1,2 +1,6 @@
 pub mod value;
-pub mod expr;
\ No newline at end of file
+pub mod expr;
+pub mod error;
+pub mod class;
+pub mod enumeration;
+pub mod pattern;
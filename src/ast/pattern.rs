@@ -0,0 +1,196 @@
+use std::{cell::RefCell, fmt, rc::Rc};
+
+use crate::{interpreter::environment::Environment, lexer::tokens::{Span, Token}};
+
+use super::{error::RuntimeError, expr::Expr, value::Value};
+
+/// The left side of a match arm, what a value has to look like for the arm to run.
+pub enum Pattern {
+    Literal {        // `0`, `-1`, `"text"`, `true`, `null`
+        value: Value,
+        span: Span
+    },
+    Wildcard {       // `_`
+        span: Span
+    },
+    Binding {        // any other name, matches everything and binds it
+        name: Token,
+        span: Span
+    },
+    Array {          // `[a, 0, _]`, only arrays of exactly that length
+        elements: Vec<Pattern>,
+        span: Span
+    },
+    Object {         // `{ name, age: 0 }`, other fields are ignored
+        fields: Vec<(Token, Pattern)>,
+        span: Span
+    },
+    Variant {        // `Shape.Circle(r)`, or `Shape.Circle` for any payload
+        enumeration: Token,
+        variant: Box<Token>,
+        fields: Option<Vec<Pattern>>,
+        span: Span
+    },
+}
+
+/// One `pattern if guard => body` of a match.
+pub struct Arm {
+    pub pattern: Pattern,
+    pub guard: Option<Expr>,
+    pub body: Expr,
+}
+
+impl Pattern {
+
+    /// The part of the source the pattern was parsed from.
+    pub fn span(&self) -> Span {
+        match self {
+            Pattern::Literal { span, .. }
+            | Pattern::Wildcard { span }
+            | Pattern::Binding { span, .. }
+            | Pattern::Array { span, .. }
+            | Pattern::Object { span, .. }
+            | Pattern::Variant { span, .. } => *span,
+        }
+    }
+
+    /// Whether `value` fits the pattern, defining the names it binds in `scope` as it goes. A
+    /// failed match may leave some of them behind, so each arm gets a fresh scope.
+    pub fn matches(&self, value: &Value, scope: &Rc<RefCell<Environment>>) -> Result<bool, RuntimeError> {
+        match (self, value) {
+            (Pattern::Literal { value: literal, .. }, _) => Ok(literal == value),
+            (Pattern::Wildcard { .. }, _) => Ok(true),
+            (Pattern::Binding { name, .. }, _) => {
+                scope.borrow_mut().define(&name.lexeme, value.clone());
+                Ok(true)
+            },
+            (Pattern::Array { elements, .. }, Value::Array(values)) => {
+                if elements.len() != values.len() {
+                    return Ok(false);
+                }
+                for (element, value) in elements.iter().zip(values) {
+                    if !element.matches(value, scope)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            },
+            (Pattern::Object { fields, .. }, Value::Object(_) | Value::Instance(_)) => {
+                for (name, pattern) in fields {
+                    let field = match value {
+                        Value::Object(values) => values.get(&name.lexeme).cloned(),
+                        Value::Instance(instance) => instance.borrow().fields.get(&name.lexeme).cloned(),
+                        _ => None,
+                    };
+                    match field {
+                        Some(field) if pattern.matches(&field, scope)? => (),
+                        _ => return Ok(false),
+                    }
+                }
+                Ok(true)
+            },
+            (Pattern::Variant { enumeration, variant, fields, .. }, Value::Variant { enumeration: owner, variant: name, values }) => {
+                // the enum is looked up when matching, like any other variable
+                let expected = match scope.borrow().get(enumeration)? {
+                    Value::Enum(expected) => expected,
+                    _ => return Ok(false),
+                };
+                if !Rc::ptr_eq(&expected, owner) || variant.lexeme != *name {
+                    return Ok(false);
+                }
+
+                match fields {
+                    None => Ok(true),
+                    Some(fields) if fields.len() != values.len() => Ok(false),
+                    Some(fields) => {
+                        for (field, value) in fields.iter().zip(values) {
+                            if !field.matches(value, scope)? {
+                                return Ok(false);
+                            }
+                        }
+                        Ok(true)
+                    },
+                }
+            },
+            _ => Ok(false),
+        }
+    }
+}
+
+// how the pattern is written, for tests and warnings
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |patterns: &[Pattern]| patterns.iter().map(|pattern| pattern.to_string()).collect::<Vec<_>>().join(", ");
+
+        match self {
+            Pattern::Literal { value: Value::String(s), .. } => write!(f, "\"{}\"", s),
+            Pattern::Literal { value, .. } => write!(f, "{}", value),
+            Pattern::Wildcard { .. } => write!(f, "_"),
+            Pattern::Binding { name, .. } => write!(f, "{}", name.lexeme),
+            Pattern::Array { elements, .. } => write!(f, "[{}]", join(elements)),
+            Pattern::Object { fields, .. } => {
+                let fields: Vec<String> = fields.iter().map(|(name, pattern)| match pattern {
+                    Pattern::Binding { name: binding, .. } if binding.lexeme == name.lexeme => name.lexeme.clone(),
+                    _ => format!("{}: {}", name.lexeme, pattern),
+                }).collect();
+                write!(f, "{{ {} }}", fields.join(", "))
+            },
+            Pattern::Variant { enumeration, variant, fields, .. } => {
+                write!(f, "{}.{}", enumeration.lexeme, variant.lexeme)?;
+                match fields {
+                    Some(fields) => write!(f, "({})", join(fields)),
+                    None => Ok(()),
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::lexer::tokens::TokenKind;
+
+    fn identifier(name: &str) -> Token {
+        Token::dynamic_tokenkind("filename".to_string(), TokenKind::Identifier, name.to_string(), 1, 1).unwrap()
+    }
+
+    fn binding(name: &str) -> Pattern {
+        Pattern::Binding { name: identifier(name), span: Span::new(0, 0) }
+    }
+
+    #[test]
+    fn test_array_pattern_binds_elements() {
+        let pattern = Pattern::Array {
+            elements: vec![binding("a"), Pattern::Literal { value: Value::Number(2.0), span: Span::new(0, 0) }],
+            span: Span::new(0, 0),
+        };
+        let scope = Environment::new();
+
+        assert_eq!(pattern.matches(&Value::Array(vec![Value::Number(1.0), Value::Number(2.0)]), &scope), Ok(true));
+        assert_eq!(scope.borrow().get(&identifier("a")), Ok(Value::Number(1.0)));
+
+        assert_eq!(pattern.matches(&Value::Array(vec![Value::Number(1.0)]), &scope), Ok(false));
+        assert_eq!(pattern.matches(&Value::Array(vec![Value::Number(1.0), Value::Number(3.0)]), &scope), Ok(false));
+        assert_eq!(pattern.to_string(), "[a, 2]");
+    }
+
+    #[test]
+    fn test_object_pattern_ignores_other_fields() {
+        let pattern = Pattern::Object { fields: vec![(identifier("name"), binding("name"))], span: Span::new(0, 0) };
+        let scope = Environment::new();
+
+        let object = Value::Object(HashMap::from([
+            ("name".to_string(), Value::String("x".to_string())),
+            ("age".to_string(), Value::Number(3.0)),
+        ]));
+        assert_eq!(pattern.matches(&object, &scope), Ok(true));
+        assert_eq!(scope.borrow().get(&identifier("name")), Ok(Value::String("x".to_string())));
+
+        assert_eq!(pattern.matches(&Value::Object(HashMap::new()), &scope), Ok(false));
+        assert_eq!(pattern.matches(&Value::Number(1.0), &scope), Ok(false));
+        assert_eq!(pattern.to_string(), "{ name }");
+    }
+}
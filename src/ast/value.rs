@@ -1,9 +1,24 @@
-use std::{collections::HashMap, fmt, rc::Rc};
+use std::{cell::{Cell, RefCell}, collections::HashMap, fmt, rc::Rc};
 
-use crate::lexer::tokens::{Token, TokenKind};
+use crate::{interpreter::environment::Environment, lexer::tokens::{Span, Token, TokenKind}, stmt::stmt::{Flow, Stmt}};
+
+use super::{class::{Class, Instance}, enumeration::Enum, error::{RuntimeError, RuntimeErrorKind}};
+
+/// How deep calls to declared functions can nest before they fail with a stack overflow, instead
+/// of overflowing the stack of the interpreter itself.
+pub const MAX_CALL_DEPTH: usize = 1000;
+
+thread_local! {
+    // calls to declared functions that havent returned yet
+    static CALL_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// The body of a callable, given its arguments.
+pub type CallableFn = dyn Fn(&Vec<Value>) -> Result<Value, RuntimeError>;
 
 #[derive(Clone)]
 pub enum Value {
+    Null,
     Number(f64),
     String(String),
     Boolean(bool),
@@ -12,13 +27,22 @@ pub enum Value {
     Callable {
         name: String,
         arity: usize,
-        fun: Rc<dyn Fn(&Vec<Value>) -> Value> // this should add the environment for that
-    }
+        fun: Rc<CallableFn> // declared functions capture their environment in here
+    },
+    Class(Rc<Class>),
+    Instance(Rc<RefCell<Instance>>), // shared, every copy sees the same fields
+    Enum(Rc<Enum>),
+    Variant {
+        enumeration: Rc<Enum>,
+        variant: String,
+        values: Vec<Value>, // the payload, in the order of the declared fields
+    },
 }
 
 impl fmt::Debug for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            Value::Null => write!(f, "Null"),
             Value::Number(n) => write!(f, "Number({})", n),
             Value::String(s) => write!(f, "String(\"{}\")", s),
             Value::Boolean(b) => write!(f, "Boolean({})", b),
@@ -34,7 +58,7 @@ impl fmt::Debug for Value {
             }
             Value::Object(obj) => {
                 write!(f, "Object({{")?;
-                for (i, (key, value)) in obj.iter().enumerate() {
+                for (i, (key, value)) in sorted(obj).into_iter().enumerate() {
                     if i > 0 {
                         write!(f, ", ")?;
                     }
@@ -43,61 +67,246 @@ impl fmt::Debug for Value {
                 write!(f, "}})")
             }
             Value::Callable { name, arity, .. } => write!(f, "Callable(name: {}, arity: {})", name, arity),
+            Value::Class(class) => write!(f, "Class({})", class.name),
+            Value::Instance(instance) => write!(f, "Instance({})", instance.borrow().class.name),
+            Value::Enum(enumeration) => write!(f, "Enum({})", enumeration.name),
+            Value::Variant { enumeration, variant, values } => write!(f, "Variant({}.{}{:?})", enumeration.name, variant, values),
+        }
+    }
+}
+
+// how a value looks when it ends up inside a string
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Number(n) => write!(f, "{}", n),
+            Value::String(s) => write!(f, "{}", s),
+            Value::Boolean(b) => write!(f, "{}", b),
+            Value::Array(arr) => {
+                write!(f, "[")?;
+                for (i, value) in arr.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+            Value::Object(obj) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in sorted(obj).into_iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", key, value)?;
+                }
+                write!(f, "}}")
+            }
+            Value::Callable { name, .. } => write!(f, "<fn {}>", name),
+            Value::Class(class) => write!(f, "<class {}>", class.name),
+            Value::Instance(instance) => write!(f, "<{} instance>", instance.borrow().class.name),
+            Value::Enum(enumeration) => write!(f, "<enum {}>", enumeration.name),
+            Value::Variant { enumeration, variant, values } => {
+                write!(f, "{}.{}", enumeration.name, variant)?;
+                if !values.is_empty() {
+                    let values: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+                    write!(f, "({})", values.join(", "))?;
+                }
+                Ok(())
+            }
         }
     }
 }
 
+// the fields of an object by name, so printing one does not depend on the order of the map
+fn sorted(fields: &HashMap<String, Value>) -> Vec<(&String, &Value)> {
+    let mut sorted: Vec<(&String, &Value)> = fields.iter().collect();
+    sorted.sort_by_key(|(key, _)| *key);
+    sorted
+}
+
 impl PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
+            (Value::Null, Value::Null) => true,
             (Value::Number(a), Value::Number(b)) => a == b,
             (Value::String(a), Value::String(b)) => a == b,
             (Value::Boolean(a), Value::Boolean(b)) => a == b,
             (Value::Array(a), Value::Array(b)) => a == b,
             (Value::Object(a), Value::Object(b)) => a == b,
+            // callables, classes and instances are only equal to themselves
+            (Value::Callable { fun: a, .. }, Value::Callable { fun: b, .. }) => Rc::ptr_eq(a, b),
+            (Value::Class(a), Value::Class(b)) => Rc::ptr_eq(a, b),
+            (Value::Instance(a), Value::Instance(b)) => Rc::ptr_eq(a, b),
+            (Value::Enum(a), Value::Enum(b)) => Rc::ptr_eq(a, b),
+            // variants are equal when they come from the same enum and carry equal values
             (
-                Value::Callable { name: a_name, arity: a_arity, .. },
-                Value::Callable { name: b_name, arity: b_arity, .. },
-            ) => a_name == b_name && a_arity == b_arity,
+                Value::Variant { enumeration: a_enum, variant: a_variant, values: a_values },
+                Value::Variant { enumeration: b_enum, variant: b_variant, values: b_values },
+            ) => Rc::ptr_eq(a_enum, b_enum) && a_variant == b_variant && a_values == b_values,
             _ => false,
         }
     }
 }
 
 impl Value {
-    pub fn apply_unary_op(&self, token: &Token) -> Result<Value, String> {
+    pub fn apply_unary_op(&self, token: &Token) -> Result<Value, RuntimeError> {
         match self {
             Value::Number(x) => match token.kind {
                 TokenKind::Minus => Ok(Value::Number(-x)),
                 TokenKind::Plus => Ok(Value::Number(*x)), // Unary plus (no-op)
-                _ => Err(format!(
-                    "Unsupported unary operator {:?} for Number at line {}, column {}",
-                    token.kind, token.line_number, token.column_number
-                )),
+                TokenKind::Bang => Ok(Value::Boolean(!self.is_truthy())),
+                _ => Err(self.unsupported_unary(token)),
             },
-            Value::Boolean(b) => match token.kind {
-                TokenKind::Bang => Ok(Value::Boolean(!b)), // Logical NOT
-                _ => Err(format!(
-                    "Unsupported unary operator {:?} for Boolean at line {}, column {}",
-                    token.kind, token.line_number, token.column_number
-                )),
+            _ => match token.kind {
+                TokenKind::Bang => Ok(Value::Boolean(!self.is_truthy())), // Logical NOT
+                _ => Err(self.unsupported_unary(token)),
             },
-            Value::Array(arr) => match token.kind {
-                TokenKind::Bang => Ok(Value::Boolean(arr.is_empty())),
-                _ => Err(format!(
-                    "Unsupported unary operator {:?} for Array at line {}, column {}",
-                    token.kind, token.line_number, token.column_number
-                ))
+        }
+    }
+
+    /// A callable running `body` with `params` bound to its arguments, in a new scope inside
+    /// `closure`, the environment the function was created in.
+    pub fn function(name: &str, params: &[Token], body: &Rc<[Stmt]>, closure: &Rc<RefCell<Environment>>) -> Value {
+        let closure = closure.clone();
+        let params: Vec<String> = params.iter().map(|param| param.lexeme.clone()).collect();
+        let body = body.clone();
+
+        Value::Callable {
+            name: name.to_string(),
+            arity: params.len(),
+            fun: Rc::new(move |arguments| {
+                let depth = CALL_DEPTH.get();
+                if depth == MAX_CALL_DEPTH {
+                    return Err(RuntimeError::unlocated(RuntimeErrorKind::StackOverflow(MAX_CALL_DEPTH))
+                        .with_help("check that the recursion reaches a case that returns without calling again"));
+                }
+
+                let scope = Environment::with_enclosing(closure.clone());
+                for (param, argument) in params.iter().zip(arguments) {
+                    scope.borrow_mut().define(param, argument.clone());
+                }
+
+                CALL_DEPTH.set(depth + 1);
+                let result = Self::run(&body, &scope);
+                CALL_DEPTH.set(depth);
+                result
+            })
+        }
+    }
+
+    // runs a function body, giving what it returns
+    fn run(body: &[Stmt], scope: &Rc<RefCell<Environment>>) -> Result<Value, RuntimeError> {
+        for statement in body {
+            if let Flow::Return(value) = statement.execute(scope)? {
+                return Ok(value);
             }
-            _ => Err(format!(
-                "Unary operator {:?} is not supported for this Value type at line {}, column {}",
-                token.kind, token.line_number, token.column_number
-            )),
+        }
+        Ok(Value::Null)
+    }
+
+    /// The element at `index`, negative indices count from the end, or the field named `index` of
+    /// an object. `bracket` and `span` locate errors, `span` being the index expression.
+    pub fn index(&self, index: &Value, bracket: &Token, span: Span) -> Result<Value, RuntimeError> {
+        match self {
+            Value::Array(arr) => Ok(arr[Self::position(index, arr.len(), bracket, span)?].clone()),
+            Value::Object(fields) => {
+                let key = Self::key(index, bracket, span)?;
+                fields.get(&key).cloned().ok_or_else(|| {
+                    RuntimeError::new(RuntimeErrorKind::UndefinedProperty(key), span, bracket.line_number, bracket.column_number)
+                })
+            },
+            _ => Err(RuntimeError::new(RuntimeErrorKind::NotIndexable(self.type_name()), span, bracket.line_number, bracket.column_number)),
+        }
+    }
+
+    pub fn set_index(&mut self, index: &Value, value: Value, bracket: &Token, span: Span) -> Result<(), RuntimeError> {
+        match self {
+            Value::Array(arr) => {
+                let position = Self::position(index, arr.len(), bracket, span)?;
+                arr[position] = value;
+                Ok(())
+            },
+            Value::Object(fields) => {
+                fields.insert(Self::key(index, bracket, span)?, value);
+                Ok(())
+            },
+            _ => Err(RuntimeError::new(RuntimeErrorKind::NotIndexable(self.type_name()), span, bracket.line_number, bracket.column_number)),
+        }
+    }
+
+    /// The elements from `start` up to but not including `end`, missing bounds mean the ends of the
+    /// array. Bounds can be negative and are clamped to the array instead of failing.
+    pub fn slice(&self, start: Option<&Value>, end: Option<&Value>, bracket: &Token, span: Span) -> Result<Value, RuntimeError> {
+        let arr = match self {
+            Value::Array(arr) => arr,
+            _ => return Err(RuntimeError::new(RuntimeErrorKind::NotIndexable(self.type_name()), span, bracket.line_number, bracket.column_number)),
+        };
+
+        let length = arr.len() as i64;
+        let bound = |value: Option<&Value>, default: i64| -> Result<usize, RuntimeError> {
+            let bound = match value {
+                Some(value) => Self::whole_number(value, bracket, span)?,
+                None => default,
+            };
+            let bound = if bound < 0 { bound + length } else { bound };
+            Ok(bound.clamp(0, length) as usize)
+        };
+
+        let start = bound(start, 0)?;
+        let end = bound(end, length)?;
+
+        Ok(Value::Array(if start < end { arr[start..end].to_vec() } else { vec![] }))
+    }
+
+    // where `index` points in something of `length` elements
+    fn position(index: &Value, length: usize, bracket: &Token, span: Span) -> Result<usize, RuntimeError> {
+        let index = Self::whole_number(index, bracket, span)?;
+        let position = if index < 0 { index + length as i64 } else { index };
+
+        if position < 0 || position >= length as i64 {
+            return Err(RuntimeError::new(RuntimeErrorKind::IndexOutOfBounds { index, length }, span, bracket.line_number, bracket.column_number));
+        }
+        Ok(position as usize)
+    }
+
+    /// The field name `key` stands for, only strings can be object keys.
+    pub fn key(key: &Value, bracket: &Token, span: Span) -> Result<String, RuntimeError> {
+        match key {
+            Value::String(key) => Ok(key.clone()),
+            other => Err(RuntimeError::new(RuntimeErrorKind::InvalidKey(other.type_name()), span, bracket.line_number, bracket.column_number)),
+        }
+    }
+
+    fn whole_number(index: &Value, bracket: &Token, span: Span) -> Result<i64, RuntimeError> {
+        match index {
+            Value::Number(n) if n.fract() == 0.0 => Ok(*n as i64),
+            Value::Number(n) => Err(RuntimeError::new(RuntimeErrorKind::InvalidIndex(n.to_string()), span, bracket.line_number, bracket.column_number)),
+            other => Err(RuntimeError::new(RuntimeErrorKind::InvalidIndex(other.type_name().to_string()), span, bracket.line_number, bracket.column_number)),
+        }
+    }
+
+    /// Whether the value counts as true in a condition: `null`, `false`, `0` and empty strings,
+    /// arrays and objects are false, everything else is true.
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Null => false,
+            Value::Number(n) => *n != 0.0 && !n.is_nan(),
+            Value::String(s) => !s.is_empty(),
+            Value::Boolean(b) => *b,
+            Value::Array(arr) => !arr.is_empty(),
+            Value::Object(obj) => !obj.is_empty(),
+            Value::Callable { .. } | Value::Class(_) | Value::Instance(_) | Value::Enum(_) | Value::Variant { .. } => true,
         }
     }
 
-    pub fn apply_binary_op(&self, token: &Token, other: &Value) -> Result<Value, String> {
+    pub fn apply_binary_op(&self, token: &Token, other: &Value) -> Result<Value, RuntimeError> {
         match (self, other) {
+            // Any two values can be compared, values of different types are never equal
+            _ if token.kind == TokenKind::EqualEqual => Ok(Value::Boolean(self == other)),
+            _ if token.kind == TokenKind::BangEqual => Ok(Value::Boolean(self != other)),
+
             // Arithmetic operations for numbers
             (Value::Number(lhs), Value::Number(rhs)) => match token.kind {
                 TokenKind::Plus => Ok(Value::Number(lhs + rhs)),
@@ -105,40 +314,29 @@ impl Value {
                 TokenKind::Star => Ok(Value::Number(lhs * rhs)),
                 TokenKind::Slash => {
                     if *rhs == 0.0 {
-                        Err(format!("Division by zero ocurred at line {}, column: {}", token.line_number, token.column_number))
+                        Err(RuntimeError::at(RuntimeErrorKind::DivisionByZero, token))
                     } else {
                         Ok(Value::Number(lhs / rhs))
                     }
                 }
+                TokenKind::Percent => {
+                    if *rhs == 0.0 {
+                        Err(RuntimeError::at(RuntimeErrorKind::DivisionByZero, token))
+                    } else {
+                        Ok(Value::Number(lhs % rhs))
+                    }
+                }
                 TokenKind::Greater => Ok(Value::Boolean(lhs > rhs)),
                 TokenKind::GreaterEqual => Ok(Value::Boolean(lhs >= rhs)),
                 TokenKind::Less => Ok(Value::Boolean(lhs < rhs)),
                 TokenKind::LessEqual => Ok(Value::Boolean(lhs <= rhs)),
-                TokenKind::EqualEqual => Ok(Value::Boolean(lhs == rhs)),
-                TokenKind::BangEqual => Ok(Value::Boolean(lhs != rhs)),
-                _ => Err(format!(
-                    "Unsupported binary operator {:?} for Numbers at line {}, column {}",
-                    token.kind, token.line_number, token.column_number
-                )),
-            },
-
-            // Logical operations for booleans
-            (Value::Boolean(lhs), Value::Boolean(rhs)) => match token.kind {
-                TokenKind::And => Ok(Value::Boolean(*lhs && *rhs)),
-                TokenKind::Or => Ok(Value::Boolean(*lhs || *rhs)),
-                _ => Err(format!(
-                    "Unsupported binary operator {:?} for Booleans at line {}, column {}",
-                    token.kind, token.line_number, token.column_number
-                )),
+                _ => Err(self.unsupported_binary(token, other)),
             },
 
             // String concatenation
             (Value::String(lhs), Value::String(rhs)) => match token.kind {
                 TokenKind::Plus => Ok(Value::String(format!("{}{}", lhs, rhs))),
-                _ => Err(format!(
-                    "Unsupported binary operator {:?} for Strings at line {}, column {}",
-                    token.kind, token.line_number, token.column_number
-                )),
+                _ => Err(self.unsupported_binary(token, other)),
             },
 
             // Array concatenation (WHAT??)
@@ -148,17 +346,43 @@ impl Value {
                     new_array.extend(rhs.clone());
                     Ok(Value::Array(new_array))
                 },
-                _ => Err(format!(
-                    "Unsupported binary operator {:?} for Arrays at line {}, column {}",
-                    token.kind, token.line_number, token.column_number
-                )),
+                _ => Err(self.unsupported_binary(token, other)),
             }
 
             // Unsupported types or mismatches
-            _ => Err(format!(
-                "Binary operator {:?} is not supported for the given Value types at line {}, column {}",
-                token.kind, token.line_number, token.column_number
-            )),
+            _ => Err(self.unsupported_binary(token, other)),
+        }
+    }
+
+    fn unsupported_unary(&self, token: &Token) -> RuntimeError {
+        RuntimeError::at(RuntimeErrorKind::UnsupportedUnary {
+            operator: token.lexeme.clone(),
+            operand: self.type_name(),
+        }, token)
+    }
+
+    fn unsupported_binary(&self, token: &Token, other: &Value) -> RuntimeError {
+        RuntimeError::at(RuntimeErrorKind::UnsupportedBinary {
+            operator: token.lexeme.clone(),
+            left: self.type_name(),
+            right: other.type_name(),
+        }, token)
+    }
+
+    /// Name of the variant, for error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Null => "Null",
+            Value::Number(_) => "Number",
+            Value::String(_) => "String",
+            Value::Boolean(_) => "Boolean",
+            Value::Array(_) => "Array",
+            Value::Object(_) => "Object",
+            Value::Callable { .. } => "Callable",
+            Value::Class(_) => "Class",
+            Value::Instance(_) => "Instance",
+            Value::Enum(_) => "Enum",
+            Value::Variant { .. } => "Variant",
         }
     }
 
@@ -174,7 +398,7 @@ impl Value {
                     );
                 }
             }
-            TokenKind::String => {
+            TokenKind::String | TokenKind::Interpolation => {
                 if let Some(Value::String(string)) = token.literal {
                     Self::String(string)
                 } else {
@@ -186,6 +410,7 @@ impl Value {
             }
             TokenKind::True => Self::Boolean(true),
             TokenKind::False => Self::Boolean(false),
+            TokenKind::Null => Self::Null,
             _ => panic!(
                 "Could not create Value from {:?} in line {} column {}",
                 token.lexeme, token.line_number, token.column_number
@@ -200,22 +425,6 @@ impl Value {
         }
     }
 
-    pub fn as_string(&self) -> Option<&String> {
-        if let Value::String(s) = self {
-            Some(s)
-        } else {
-            None
-        }
-    }
-
-    pub fn as_boolean(&self) -> Option<bool> {
-        if let Value::Boolean(b) = self {
-            Some(*b)
-        } else {
-            None
-        }
-    }
-
     pub fn as_array(&self) -> Option<&Vec<Value>> {
         if let Value::Array(a) = self {
             Some(a)
@@ -224,14 +433,6 @@ impl Value {
         }
     }
 
-    pub fn as_object(&self) -> Option<&HashMap<String, Value>> {
-        if let Value::Object(o) = self {
-            Some(o)
-        } else {
-            None
-        }
-    }
-
 }
 
 #[cfg(test)]
@@ -239,6 +440,17 @@ mod tests {
     use super::*;
     use std::collections::HashMap;
 
+    #[test]
+    fn test_object_fields_print_in_order() {
+        let fields: HashMap<String, Value> = ["name", "age", "city", "zip", "b", "a"].iter()
+            .map(|key| (key.to_string(), Value::Number(1.0)))
+            .collect();
+        let object = Value::Object(fields);
+
+        assert_eq!(object.to_string(), "{a: 1, age: 1, b: 1, city: 1, name: 1, zip: 1}");
+        assert_eq!(format!("{:?}", object), "Object({\"a\": Number(1), \"age\": Number(1), \"b\": Number(1), \"city\": Number(1), \"name\": Number(1), \"zip\": Number(1)})");
+    }
+
     #[test]
     fn test_token_equality() {
         let token1 = Token::new(
@@ -377,6 +589,29 @@ mod tests {
         assert_eq!(token.literal, Some(Value::Object(HashMap::new())));
     }
 
+    #[test]
+    fn test_truthiness() {
+        assert!(!Value::Null.is_truthy());
+        assert!(!Value::Number(0.0).is_truthy());
+        assert!(!Value::Number(f64::NAN).is_truthy());
+        assert!(Value::Number(-1.0).is_truthy());
+        assert!(!Value::String(String::new()).is_truthy());
+        assert!(Value::String("0".to_string()).is_truthy());
+        assert!(!Value::Boolean(false).is_truthy());
+        assert!(!Value::Array(vec![]).is_truthy());
+        assert!(Value::Array(vec![Value::Null]).is_truthy());
+        assert!(!Value::Object(HashMap::new()).is_truthy());
+        assert!(Value::Callable { name: "f".to_string(), arity: 0, fun: Rc::new(|_| Ok(Value::Null)) }.is_truthy());
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Value::Number(42.0).to_string(), "42");
+        assert_eq!(Value::Number(1.5).to_string(), "1.5");
+        assert_eq!(Value::String("hi".to_string()).to_string(), "hi");
+        assert_eq!(Value::Array(vec![Value::Number(1.0), Value::Boolean(false)]).to_string(), "[1, false]");
+    }
+
     #[test]
     fn test_eof_token() {
         let token = Token::eof("main.rs".to_string(), 10, 30);
@@ -0,0 +1,431 @@
+use std::collections::HashMap;
+
+use crate::{
+    ast::{expr::{Expr, Key}, pattern::{Arm, Pattern}},
+    lexer::tokens::Token,
+    stmt::stmt::Stmt,
+};
+
+use super::warning::{Warning, WarningKind};
+
+/// What the checker knows about a name without running anything.
+#[derive(Debug, Clone)]
+enum Known {
+    Enum(Vec<(String, usize)>), // an enum declaration, its variants with their number of fields
+    Variant(String),            // a value of the named enum
+    Other,                      // anything else, still shadows the names around it
+}
+
+/// Looks for mistakes that can be found before running: a `match` on an enum that misses some of
+/// its variants, and arms that can never run because an earlier one matches every value or
+/// because they name a variant the enum does not have.
+pub struct Checker {
+    scopes: Vec<HashMap<String, Known>>, // innermost last, the first one lasts as long as the checker
+    warnings: Vec<Warning>,
+}
+
+impl Checker {
+    pub fn new() -> Self {
+        Self {
+            scopes: vec![HashMap::new()],
+            warnings: vec![],
+        }
+    }
+
+    /// Checks `statements`, declarations are remembered for the next call like the interpreter
+    /// keeps its globals.
+    pub fn check(&mut self, statements: &[Stmt]) -> Vec<Warning> {
+        for statement in statements {
+            self.statement(statement);
+        }
+        std::mem::take(&mut self.warnings)
+    }
+
+    fn statement(&mut self, statement: &Stmt) {
+        match statement {
+            Stmt::Expression { expression, .. } => self.expression(expression),
+            Stmt::Let { name, initializer, .. } => {
+                let known = match initializer {
+                    Some(initializer) => {
+                        self.expression(initializer);
+                        self.infer(initializer).map_or(Known::Other, Known::Variant)
+                    },
+                    None => Known::Other,
+                };
+                self.define(name, known);
+            },
+            Stmt::Function { name, params, body, .. } => {
+                self.define(name, Known::Other);
+                self.function(params, body);
+            },
+            Stmt::Class { name, methods, .. } => {
+                self.define(name, Known::Other);
+                for method in methods {
+                    if let Stmt::Function { params, body, .. } = method {
+                        self.function(params, body);
+                    }
+                }
+            },
+            Stmt::Enum { name, variants, .. } => {
+                let variants = variants.iter().map(|(variant, fields)| (variant.lexeme.clone(), fields.len())).collect();
+                self.define(name, Known::Enum(variants));
+            },
+            Stmt::Block { statements, .. } => {
+                self.scopes.push(HashMap::new());
+                statements.iter().for_each(|statement| self.statement(statement));
+                self.scopes.pop();
+            },
+            Stmt::If { condition, then_branch, else_branch, .. } => {
+                self.expression(condition);
+                self.statement(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.statement(else_branch);
+                }
+            },
+            Stmt::While { condition, body, .. } => {
+                self.expression(condition);
+                self.statement(body);
+            },
+            Stmt::For { initializer, condition, increment, body, .. } => {
+                self.scopes.push(HashMap::new());
+                if let Some(initializer) = initializer {
+                    self.statement(initializer);
+                }
+                condition.iter().chain(increment).for_each(|expression| self.expression(expression));
+                self.statement(body);
+                self.scopes.pop();
+            },
+            Stmt::Return { value, .. } => {
+                if let Some(value) = value {
+                    self.expression(value);
+                }
+            },
+            Stmt::Break { .. } | Stmt::Continue { .. } => (),
+        }
+    }
+
+    fn function(&mut self, params: &[Token], body: &[Stmt]) {
+        self.scopes.push(HashMap::new());
+        params.iter().for_each(|param| self.define(param, Known::Other));
+        body.iter().for_each(|statement| self.statement(statement));
+        self.scopes.pop();
+    }
+
+    fn expression(&mut self, expression: &Expr) {
+        match expression {
+            Expr::Literal { .. } | Expr::Variable { .. } | Expr::This { .. } | Expr::Super { .. } => (),
+            Expr::Grouping { expression, .. } => self.expression(expression),
+            Expr::Binary { left, right, .. } => {
+                self.expression(left);
+                self.expression(right);
+            },
+            Expr::Unary { right, .. } => self.expression(right),
+            Expr::Conditional { condition, then_branch, else_branch, .. } => {
+                self.expression(condition);
+                self.expression(then_branch);
+                self.expression(else_branch);
+            },
+            Expr::Call { callee, arguments, .. } => {
+                self.expression(callee);
+                arguments.iter().for_each(|argument| self.expression(argument));
+            },
+            Expr::Get { object, .. } => self.expression(object),
+            Expr::Interpolation { parts, .. } => parts.iter().for_each(|part| self.expression(part)),
+            Expr::Array { elements, .. } => elements.iter().for_each(|element| self.expression(element)),
+            Expr::Object { properties, .. } => {
+                for (key, value) in properties {
+                    if let Key::Computed { key, .. } = key {
+                        self.expression(key);
+                    }
+                    self.expression(value);
+                }
+            },
+            Expr::Index { object, index, .. } => {
+                self.expression(object);
+                self.expression(index);
+            },
+            Expr::Slice { object, start, end, .. } => {
+                self.expression(object);
+                start.iter().chain(end).for_each(|bound| self.expression(bound));
+            },
+            Expr::Assign { target, value, .. } => {
+                self.expression(target);
+                self.expression(value);
+
+                // what the variable holds from now on, `+=` and friends never give an enum value
+                if let Expr::Variable { name, .. } = &**target {
+                    let known = self.infer(value).map_or(Known::Other, Known::Variant);
+                    self.assign(name, known);
+                }
+            },
+            Expr::Function { params, body, .. } => self.function(params, body),
+            Expr::Match { keyword, value, arms, .. } => {
+                self.expression(value);
+
+                for arm in arms {
+                    self.check_variants(&arm.pattern);
+                    self.scopes.push(HashMap::new());
+                    Self::bindings(&arm.pattern, &mut |name| self.define(name, Known::Other));
+                    if let Some(guard) = &arm.guard {
+                        self.expression(guard);
+                    }
+                    self.expression(&arm.body);
+                    self.scopes.pop();
+                }
+
+                self.check_match(keyword, value, arms);
+            },
+        }
+    }
+
+    fn check_match(&mut self, keyword: &Token, value: &Expr, arms: &[Arm]) {
+        // an unguarded `_` or binding matches every value, nothing after it can run
+        let mut catch_all: Option<&Arm> = None;
+
+        for arm in arms {
+            if let Some(previous) = catch_all {
+                self.warnings.push(Warning::new(WarningKind::UnreachableArm, arm.pattern.span())
+                    .with_primary_label("no value reaches this arm")
+                    .with_label(previous.pattern.span(), "this arm already matches every value")
+                    .with_help("remove this arm or move it before the one that matches everything"));
+            } else if arm.guard.is_none() && Self::irrefutable(&arm.pattern) {
+                catch_all = Some(arm);
+            }
+        }
+
+        if catch_all.is_some() {
+            return;
+        }
+
+        let Some(enumeration) = self.infer(value).or_else(|| self.enum_of_arms(arms)) else { return };
+        let Some(Known::Enum(variants)) = self.lookup(&enumeration) else { return };
+
+        let missing: Vec<String> = variants.iter()
+            .filter(|(variant, fields)| !arms.iter().any(|arm| Self::covers(arm, &enumeration, variant, *fields)))
+            .map(|(variant, _)| variant.clone())
+            .collect();
+
+        if !missing.is_empty() {
+            self.warnings.push(Warning::new(WarningKind::NonExhaustiveMatch { enumeration, missing }, keyword.span)
+                .with_help("add an arm for each missing variant or a `_ => ...` arm"));
+        }
+    }
+
+    // warns about `Shape.Square` in `pattern` when `Shape` is an enum without that variant, such an
+    // arm can never run
+    fn check_variants(&mut self, pattern: &Pattern) {
+        match pattern {
+            Pattern::Variant { enumeration, variant, fields, .. } => {
+                if let Some(Known::Enum(variants)) = self.lookup(&enumeration.lexeme) {
+                    if !variants.iter().any(|(name, _)| *name == variant.lexeme) {
+                        let names: Vec<&str> = variants.iter().map(|(name, _)| name.as_str()).collect();
+                        let help = format!("the variants of {} are {}", enumeration.lexeme, names.join(", "));
+                        self.warnings.push(Warning::new(WarningKind::UnknownVariant {
+                            enumeration: enumeration.lexeme.clone(),
+                            variant: variant.lexeme.clone(),
+                        }, variant.span)
+                            .with_help(&help));
+                    }
+                }
+                fields.iter().flatten().for_each(|field| self.check_variants(field));
+            },
+            Pattern::Array { elements, .. } => elements.iter().for_each(|element| self.check_variants(element)),
+            Pattern::Object { fields, .. } => fields.iter().for_each(|(_, field)| self.check_variants(field)),
+            Pattern::Literal { .. } | Pattern::Wildcard { .. } | Pattern::Binding { .. } => (),
+        }
+    }
+
+    // whether `arm` matches every value of `enumeration.variant`, guarded arms may not
+    fn covers(arm: &Arm, enumeration: &str, variant: &str, field_count: usize) -> bool {
+        match &arm.pattern {
+            Pattern::Variant { enumeration: name, variant: pattern_variant, fields, .. } if arm.guard.is_none() => {
+                name.lexeme == enumeration
+                    && pattern_variant.lexeme == variant
+                    && fields.as_ref().is_none_or(|fields| fields.len() == field_count && fields.iter().all(Self::irrefutable))
+            },
+            _ => false,
+        }
+    }
+
+    // the enum every arm names a variant of, when they all do and agree on which
+    fn enum_of_arms(&self, arms: &[Arm]) -> Option<String> {
+        let mut enumeration: Option<&str> = None;
+
+        for arm in arms {
+            match &arm.pattern {
+                Pattern::Variant { enumeration: name, .. } if enumeration.is_none_or(|seen| seen == name.lexeme) => {
+                    enumeration = Some(&name.lexeme);
+                },
+                _ => return None,
+            }
+        }
+        enumeration.map(str::to_string)
+    }
+
+    // the enum `expression` gives a value of, as far as can be told without running it
+    fn infer(&self, expression: &Expr) -> Option<String> {
+        match expression {
+            Expr::Grouping { expression, .. } => self.infer(expression),
+            Expr::Variable { name, .. } => match self.lookup(&name.lexeme) {
+                Some(Known::Variant(enumeration)) => Some(enumeration.clone()),
+                _ => None,
+            },
+            // `Shape.Empty`, a variant with fields would be its constructor instead
+            Expr::Get { object, name, optional: false, .. } => self.variant_of(object, name, 0),
+            // `Shape.Circle(1)`
+            Expr::Call { callee, arguments, optional: false, .. } if !arguments.is_empty() => match &**callee {
+                Expr::Get { object, name, optional: false, .. } => self.variant_of(object, name, arguments.len()),
+                _ => None,
+            },
+            Expr::Conditional { then_branch, else_branch, .. } => {
+                let enumeration = self.infer(then_branch)?;
+                (self.infer(else_branch)? == enumeration).then_some(enumeration)
+            },
+            _ => None,
+        }
+    }
+
+    fn variant_of(&self, object: &Expr, variant: &Token, field_count: usize) -> Option<String> {
+        let Expr::Variable { name, .. } = object else { return None };
+
+        match self.lookup(&name.lexeme) {
+            Some(Known::Enum(variants)) if variants.iter().any(|(name, fields)| *name == variant.lexeme && *fields == field_count) => {
+                Some(name.lexeme.clone())
+            },
+            _ => None,
+        }
+    }
+
+    fn irrefutable(pattern: &Pattern) -> bool {
+        matches!(pattern, Pattern::Wildcard { .. } | Pattern::Binding { .. })
+    }
+
+    // calls `bind` with every name `pattern` binds
+    fn bindings(pattern: &Pattern, bind: &mut dyn FnMut(&Token)) {
+        match pattern {
+            Pattern::Binding { name, .. } => bind(name),
+            Pattern::Array { elements, .. } => elements.iter().for_each(|element| Self::bindings(element, bind)),
+            Pattern::Object { fields, .. } => fields.iter().for_each(|(_, field)| Self::bindings(field, bind)),
+            Pattern::Variant { fields: Some(fields), .. } => fields.iter().for_each(|field| Self::bindings(field, bind)),
+            Pattern::Literal { .. } | Pattern::Wildcard { .. } | Pattern::Variant { fields: None, .. } => (),
+        }
+    }
+
+    fn define(&mut self, name: &Token, known: Known) {
+        self.scopes.last_mut().unwrap().insert(name.lexeme.clone(), known);
+    }
+
+    // changes the innermost declaration of `name`, if the checker has seen one
+    fn assign(&mut self, name: &Token, known: Known) {
+        if let Some(scope) = self.scopes.iter_mut().rev().find(|scope| scope.contains_key(&name.lexeme)) {
+            scope.insert(name.lexeme.clone(), known);
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Known> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lexer::{lexer::Lexer, tokens::Span}, parser::parser::Parser};
+
+    fn check(source: &str) -> Vec<Warning> {
+        let tokens = Lexer::new("filename", source.to_string()).tokenize().unwrap();
+        let statements = Parser::new(tokens).parse().ok().unwrap();
+        Checker::new().check(&statements)
+    }
+
+    fn messages(source: &str) -> Vec<String> {
+        check(source).iter().map(|warning| warning.message()).collect()
+    }
+
+    const SHAPE: &str = "enum Shape { Circle(r), Rect(w, h), Empty }";
+
+    #[test]
+    fn missing_variants_of_a_known_value() {
+        let warnings = check(&format!("{SHAPE} let s = Shape.Circle(1); let x = match s {{ Shape.Circle(r) => r }};"));
+
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].message(), "Match on Shape does not handle Shape.Rect, Shape.Empty");
+        assert_eq!(warnings[0].code(), "W0001");
+        assert_eq!(warnings[0].span, Span::new(77, 82)); // the `match` keyword
+    }
+
+    #[test]
+    fn enum_is_taken_from_the_arms() {
+        assert_eq!(
+            messages(&format!("{SHAPE} fn area(s) {{ return match s {{ Shape.Circle(r) => r * r, Shape.Empty => 0 }}; }}")),
+            vec!["Match on Shape does not handle Shape.Rect"]
+        );
+    }
+
+    #[test]
+    fn exhaustive_matches_are_fine() {
+        assert!(check(&format!("{SHAPE} fn f(s) {{ return match s {{ Shape.Circle(_) => 1, Shape.Rect(w, h) => 2, Shape.Empty => 3 }}; }}")).is_empty());
+        assert!(check(&format!("{SHAPE} fn f(s) {{ return match s {{ Shape.Circle => 1, _ => 2 }}; }}")).is_empty());
+        assert!(check(&format!("{SHAPE} fn f(s) {{ return match s {{ Shape.Empty => 1, other => other }}; }}")).is_empty());
+        // the value is not known to be a Shape and the arms are not all variants
+        assert!(check(&format!("{SHAPE} fn f(s) {{ return match s {{ Shape.Empty => 1, 0 => 2 }}; }}")).is_empty());
+    }
+
+    #[test]
+    fn guarded_and_refutable_arms_do_not_cover_a_variant() {
+        assert_eq!(
+            messages(&format!("{SHAPE} let s = Shape.Empty; match s {{ Shape.Circle(r) if r > 1 => 1, Shape.Rect(0, h) => 2, Shape.Empty => 3 }};")),
+            vec!["Match on Shape does not handle Shape.Circle, Shape.Rect"]
+        );
+    }
+
+    #[test]
+    fn unknown_variants_in_patterns() {
+        let warnings = check(&format!("{SHAPE} fn f(s) {{ return match s {{ Shape.Square => 1, [Shape.Circle(Shape.Dot)] => 2, _ => 3 }}; }}"));
+
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[0].message(), "Shape has no variant Square");
+        assert_eq!(warnings[0].code(), "W0003");
+        assert_eq!(warnings[0].span, Span::new(77, 83)); // `Square`
+        assert_eq!(warnings[0].help.as_deref(), Some("the variants of Shape are Circle, Rect, Empty"));
+        assert_eq!(warnings[1].message(), "Shape has no variant Dot");
+
+        // only names of enums the checker has seen are looked at
+        assert!(check("fn f(s) { return match s { Other.Square => 1, _ => 2 }; }").is_empty());
+    }
+
+    #[test]
+    fn reassignment_forgets_the_enum() {
+        assert!(check(&format!("{SHAPE} let s = Shape.Empty; s = 1; match s {{ Shape.Empty => 1, 1 => 2 }};")).is_empty());
+    }
+
+    #[test]
+    fn unreachable_arms_after_a_catch_all() {
+        let warnings = check("fn f(v) { return match v { 0 => 1, _ => 2, 1 => 3, x => 4 }; }");
+
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings.iter().all(|warning| warning.kind == WarningKind::UnreachableArm));
+        assert_eq!(warnings[0].span, Span::new(43, 44));
+        assert_eq!(warnings[0].primary_label.as_deref(), Some("no value reaches this arm"));
+        assert_eq!(warnings[0].labels, vec![(Span::new(35, 36), "this arm already matches every value".to_string())]);
+
+        // a guarded catch-all can still fall through
+        assert!(check("fn f(v) { return match v { x if x > 1 => 1, _ => 2 }; }").is_empty());
+    }
+
+    #[test]
+    fn matches_inside_arms_and_functions_are_checked() {
+        let source = format!("{SHAPE} let f = (s) => match s {{ Shape.Empty => match s {{ _ => 1, _ => 2 }}, _ => 0 }};");
+
+        assert_eq!(messages(&source), vec!["Unreachable match arm"]);
+    }
+
+    #[test]
+    fn declarations_are_remembered_between_checks() {
+        let mut checker = Checker::new();
+        let parse = |source: &str| Parser::new(Lexer::new("filename", source.to_string()).tokenize().unwrap()).parse().ok().unwrap();
+
+        assert!(checker.check(&parse(SHAPE)).is_empty());
+        assert_eq!(checker.check(&parse("let s = Shape.Empty; match s { Shape.Empty => 1 };")).len(), 1);
+    }
+}
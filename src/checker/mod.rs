@@ -0,0 +1,4 @@
+mod analysis;
+pub mod warning;
+
+pub use analysis::Checker;
@@ -0,0 +1,87 @@
+use crate::lexer::tokens::Span;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WarningKind {
+    NonExhaustiveMatch {
+        enumeration: String,
+        missing: Vec<String>, // variant names no arm handles, in declaration order
+    },
+    UnreachableArm,
+    UnknownVariant {
+        enumeration: String,
+        variant: String, // the name a pattern used that the enum does not declare
+    },
+}
+
+/// Something that is allowed but almost certainly a mistake, found before the program runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    pub(crate) kind: WarningKind,
+    pub(crate) span: Span,
+    pub(crate) help: Option<String>,
+    pub(crate) primary_label: Option<String>, // shown under `span` itself
+    pub(crate) labels: Vec<(Span, String)>,   // other places worth pointing at
+}
+
+impl Warning {
+    pub fn new(kind: WarningKind, span: Span) -> Self {
+        Self {
+            kind,
+            span,
+            help: None,
+            primary_label: None,
+            labels: vec![],
+        }
+    }
+
+    pub fn with_help(mut self, help: &str) -> Self {
+        self.help = Some(help.to_string());
+        self
+    }
+
+    pub fn with_primary_label(mut self, message: &str) -> Self {
+        self.primary_label = Some(message.to_string());
+        self
+    }
+
+    pub fn with_label(mut self, span: Span, message: &str) -> Self {
+        self.labels.push((span, message.to_string()));
+        self
+    }
+
+    /// Stable identifier of the kind of warning, so tools can filter on it without reading the message.
+    pub fn code(&self) -> &'static str {
+        match self.kind {
+            WarningKind::NonExhaustiveMatch { .. } => "W0001",
+            WarningKind::UnreachableArm => "W0002",
+            WarningKind::UnknownVariant { .. } => "W0003",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match &self.kind {
+            WarningKind::NonExhaustiveMatch { enumeration, missing } => {
+                let missing: Vec<String> = missing.iter().map(|variant| format!("{}.{}", enumeration, variant)).collect();
+                format!("Match on {} does not handle {}", enumeration, missing.join(", "))
+            },
+            WarningKind::UnreachableArm => "Unreachable match arm".to_string(),
+            WarningKind::UnknownVariant { enumeration, variant } => format!("{} has no variant {}", enumeration, variant),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_non_exhaustive_message() {
+        let warning = Warning::new(WarningKind::NonExhaustiveMatch {
+            enumeration: "Shape".to_string(),
+            missing: vec!["Rect".to_string(), "Empty".to_string()],
+        }, Span::new(0, 5));
+
+        assert_eq!(warning.message(), "Match on Shape does not handle Shape.Rect, Shape.Empty");
+        assert_eq!(warning.code(), "W0001");
+    }
+}
@@ -0,0 +1,328 @@
+use std::fmt::Write;
+
+use crate::{checker::warning::Warning, lexer::tokens::Span};
+
+use super::error::{Error, ErrorKind};
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub(crate) span: Span,
+    pub(crate) message: String,
+}
+
+/// Everything needed to show a problem to the user, whatever stage it came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub(crate) severity: Severity,
+    pub(crate) code: &'static str,
+    pub(crate) message: String,
+    pub(crate) span: Span,
+    pub(crate) label: Option<String>,   // shown under the primary span
+    pub(crate) secondary: Vec<Label>,
+    pub(crate) help: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, code: &'static str, message: String, span: Span) -> Self {
+        Self {
+            severity,
+            code,
+            message,
+            span,
+            label: None,
+            secondary: vec![],
+            help: None,
+        }
+    }
+
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.label = Some(label.to_string());
+        self
+    }
+
+    pub fn with_secondary(mut self, span: Span, message: &str) -> Self {
+        self.secondary.push(Label { span, message: message.to_string() });
+        self
+    }
+
+    pub fn with_help(mut self, help: Option<&String>) -> Self {
+        self.help = help.cloned();
+        self
+    }
+}
+
+impl<K: ErrorKind> From<&Error<K>> for Diagnostic {
+    fn from(error: &Error<K>) -> Self {
+        let mut diagnostic = Diagnostic::new(Severity::Error, error.code(), error.message(), error.span)
+            .with_help(error.help.as_ref());
+
+        for (span, message) in &error.labels {
+            diagnostic = diagnostic.with_secondary(*span, message);
+        }
+        diagnostic
+    }
+}
+
+impl From<&Warning> for Diagnostic {
+    fn from(warning: &Warning) -> Self {
+        let mut diagnostic = Diagnostic::new(Severity::Warning, warning.code(), warning.message(), warning.span)
+            .with_help(warning.help.as_ref());
+
+        if let Some(label) = &warning.primary_label {
+            diagnostic = diagnostic.with_label(label);
+        }
+        for (span, message) in &warning.labels {
+            diagnostic = diagnostic.with_secondary(*span, message);
+        }
+        diagnostic
+    }
+}
+
+/// Renders diagnostics against the source they point into, with the offending lines and
+/// carets under the exact spans.
+pub struct Renderer<'a> {
+    source_file: &'a str,
+    source: &'a str,
+    colour: bool,
+}
+
+// where a span lands on one line of the source, columns are 0 based display columns
+struct Mark<'a> {
+    line: usize,
+    start: usize,
+    end: usize,
+    primary: bool,
+    message: Option<&'a str>,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(source_file: &'a str, source: &'a str) -> Self {
+        Self {
+            source_file,
+            source,
+            colour: false,
+        }
+    }
+
+    pub fn with_colour(mut self, colour: bool) -> Self {
+        self.colour = colour;
+        self
+    }
+
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let mut out = String::new();
+        let lines: Vec<&str> = self.source.split('\n').collect();
+
+        let mut marks = self.marks(diagnostic.span, true, diagnostic.label.as_deref(), &lines);
+        for label in &diagnostic.secondary {
+            marks.extend(self.marks(label.span, false, Some(&label.message), &lines));
+        }
+
+        let (line, column) = self.location(diagnostic.span.start);
+        let gutter = " ".repeat(marks.iter().map(|m| m.line + 1).max().unwrap_or(line).to_string().len());
+        let (severity, colour) = match diagnostic.severity {
+            Severity::Error => ("error", RED),
+            Severity::Warning => ("warning", YELLOW),
+        };
+
+        let _ = writeln!(out, "{}{}",
+            self.paint(&format!("{}[{}]", severity, diagnostic.code), colour),
+            self.paint(&format!(": {}", diagnostic.message), BOLD)
+        );
+        let _ = writeln!(out, "{}{} {}:{}:{}", gutter, self.paint("-->", BLUE), self.source_file, line, column);
+        let _ = writeln!(out, "{} {}", gutter, self.paint("|", BLUE));
+
+        let mut shown: Vec<usize> = marks.iter().map(|m| m.line).collect();
+        shown.sort();
+        shown.dedup();
+
+        for (i, &number) in shown.iter().enumerate() {
+            if i > 0 && number > shown[i - 1] + 1 {
+                let _ = writeln!(out, "{}", self.paint("...", BLUE));
+            }
+
+            let text = lines.get(number).copied().unwrap_or("").replace('\t', "    ");
+            let _ = writeln!(out, "{} {} {}",
+                self.paint(&format!("{:>width$}", number + 1, width = gutter.len()), BLUE),
+                self.paint("|", BLUE),
+                text.trim_end_matches('\r')
+            );
+
+            // the primary mark goes first so its message ends up next to the carets
+            let mut on_line: Vec<&Mark> = marks.iter().filter(|m| m.line == number).collect();
+            on_line.sort_by_key(|m| !m.primary);
+
+            for mark in on_line {
+                let underline = if mark.primary { "^" } else { "-" }.repeat(mark.end - mark.start);
+                let underline = self.paint(&underline, if mark.primary { colour } else { BLUE });
+                let message = mark.message.map(|m| format!(" {}", m)).unwrap_or_default();
+
+                let _ = writeln!(out, "{} {} {}{}{}",
+                    gutter,
+                    self.paint("|", BLUE),
+                    " ".repeat(mark.start),
+                    underline,
+                    message
+                );
+            }
+        }
+
+        if let Some(help) = &diagnostic.help {
+            let _ = writeln!(out, "{} {} help: {}", gutter, self.paint("=", BLUE), help);
+        }
+
+        out
+    }
+
+    // 1 based line and column of a byte offset
+    fn location(&self, offset: usize) -> (usize, usize) {
+        let before = &self.source[..offset.min(self.source.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+        (line, column)
+    }
+
+    // a span crossing lines gets a mark on each one, the message goes on the last
+    fn marks<'b>(&self, span: Span, primary: bool, message: Option<&'b str>, lines: &[&str]) -> Vec<Mark<'b>> {
+        let (first, _) = self.location(span.start);
+        let (last, _) = self.location(span.end.max(span.start + 1) - 1);
+        let mut marks = vec![];
+        let mut line_start = lines[..first - 1].iter().map(|l| l.len() + 1).sum::<usize>();
+
+        for number in first..=last {
+            let text = lines.get(number - 1).copied().unwrap_or("");
+            let from = span.start.saturating_sub(line_start).min(text.len());
+            let to = span.end.saturating_sub(line_start).min(text.len());
+
+            let start = display_width(&text[..from]);
+            let end = display_width(&text[..to]).max(start + 1); // empty spans still get a caret
+
+            marks.push(Mark {
+                line: number - 1,
+                start,
+                end,
+                primary,
+                message: if number == last { message } else { None },
+            });
+            line_start += text.len() + 1;
+        }
+        marks
+    }
+
+    fn paint(&self, text: &str, colour: &str) -> String {
+        if self.colour {
+            format!("{}{}{}", colour, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+fn display_width(text: &str) -> usize {
+    text.chars().map(|c| if c == '\t' { 4 } else { 1 }).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{checker::Checker, lexer::lexer::Lexer, parser::parser::Parser};
+
+    #[test]
+    fn render_lex_error() {
+        let source = "let x = 1;\nlet s = \"a\\qb\";\n";
+        let errors = Lexer::new("main.ls", source.to_string()).tokenize().unwrap_err();
+
+        let rendered = Renderer::new("main.ls", source).render(&Diagnostic::from(&errors[0]));
+
+        assert_eq!(rendered, "\
+error[L0103]: Invalid escape sequence '\\q'
+ --> main.ls:2:11
+  |
+2 | let s = \"a\\qb\";
+  |           ^^
+  = help: valid escapes are \\n \\t \\r \\0 \\\\ \\\" \\$ and \\u{...}
+");
+    }
+
+    #[test]
+    fn render_secondary_label() {
+        let source = "{\n  1;\n";
+        let tokens = Lexer::new("main.ls", source.to_string()).tokenize().unwrap();
+        let errors = Parser::new(tokens).parse().err().unwrap();
+
+        let rendered = Renderer::new("main.ls", source).render(&Diagnostic::from(&errors[0]));
+
+        assert_eq!(rendered, "\
+error[P0001]: Expected '}' after block, found end of file
+ --> main.ls:3:1
+  |
+1 | {
+  | - opening brace here
+...
+3 | 
+  | ^
+");
+    }
+
+    #[test]
+    fn render_unreachable_arm() {
+        let source = "match x { _ => 1, 0 => 2 };";
+        let tokens = Lexer::new("main.ls", source.to_string()).tokenize().unwrap();
+        let statements = Parser::new(tokens).parse().unwrap();
+        let warnings = Checker::new().check(&statements);
+
+        let rendered = Renderer::new("main.ls", source).render(&Diagnostic::from(&warnings[0]));
+
+        assert_eq!(rendered, "\
+warning[W0002]: Unreachable match arm
+ --> main.ls:1:19
+  |
+1 | match x { _ => 1, 0 => 2 };
+  |                   ^ no value reaches this arm
+  |           - this arm already matches every value
+  = help: remove this arm or move it before the one that matches everything
+");
+    }
+
+    #[test]
+    fn render_multi_line_span() {
+        let source = "x = \"ab\ncd";
+        let diagnostic = Diagnostic::new(Severity::Warning, "L0100", "Unterminated string starting".to_string(), Span::new(4, 10))
+            .with_label("string starts here");
+
+        let rendered = Renderer::new("main.ls", source).render(&diagnostic);
+
+        assert_eq!(rendered, "\
+warning[L0100]: Unterminated string starting
+ --> main.ls:1:5
+  |
+1 | x = \"ab
+  |     ^^^
+2 | cd
+  | ^^ string starts here
+");
+    }
+
+    #[test]
+    fn render_with_colour() {
+        let source = "@";
+        let errors = Lexer::new("main.ls", source.to_string()).tokenize().unwrap_err();
+
+        let rendered = Renderer::new("main.ls", source).with_colour(true).render(&Diagnostic::from(&errors[0]));
+
+        assert!(rendered.starts_with("\x1b[1;31merror[L0001]\x1b[0m"));
+        assert!(rendered.contains("\x1b[1;31m^\x1b[0m"));
+    }
+}
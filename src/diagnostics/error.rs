@@ -0,0 +1,85 @@
+use std::{fmt, ops::Deref};
+
+use crate::lexer::tokens::{Span, Token};
+
+/// What went wrong, each stage has its own enum of kinds and shares [`Error`] for the rest.
+pub trait ErrorKind {
+    /// Stable identifier of the kind of error, so tools can filter on it without reading the message.
+    fn code(&self) -> &'static str;
+
+    /// The message without its location.
+    fn message(&self) -> String;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorInner<K> {
+    pub(crate) kind: K,
+    pub(crate) span: Span,
+    pub(crate) line_number: usize,
+    pub(crate) column_number: usize,
+    pub(crate) help: Option<String>,
+    pub(crate) labels: Vec<(Span, String)>, // other places worth pointing at, like where a brace was opened
+}
+
+/// An error of any stage together with where it happened. The payload is boxed so a `Result`
+/// carrying one stays as small as its `Ok` side.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error<K>(Box<ErrorInner<K>>);
+
+impl<K: ErrorKind> Error<K> {
+    pub fn new(kind: K, span: Span, line_number: usize, column_number: usize) -> Self {
+        Self(Box::new(ErrorInner {
+            kind,
+            span,
+            line_number,
+            column_number,
+            help: None,
+            labels: vec![],
+        }))
+    }
+
+    /// Error located at `token`.
+    pub fn at(kind: K, token: &Token) -> Self {
+        Self::new(kind, token.span, token.line_number, token.column_number)
+    }
+
+    pub fn with_help(mut self, help: &str) -> Self {
+        self.0.help = Some(help.to_string());
+        self
+    }
+
+    pub fn with_label(mut self, span: Span, message: &str) -> Self {
+        self.0.labels.push((span, message.to_string()));
+        self
+    }
+
+    /// The same error moved to another place in the source.
+    pub fn located_at(mut self, span: Span, line_number: usize, column_number: usize) -> Self {
+        self.0.span = span;
+        self.0.line_number = line_number;
+        self.0.column_number = column_number;
+        self
+    }
+
+    pub fn code(&self) -> &'static str {
+        self.0.kind.code()
+    }
+
+    pub fn message(&self) -> String {
+        self.0.kind.message()
+    }
+}
+
+impl<K> Deref for Error<K> {
+    type Target = ErrorInner<K>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<K: ErrorKind> fmt::Display for Error<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} in line {} column {}", self.message(), self.line_number, self.column_number)
+    }
+}
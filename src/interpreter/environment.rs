@@ -0,0 +1,145 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::{ast::{error::{RuntimeError, RuntimeErrorKind}, value::Value}, lexer::tokens::Token};
+
+/// The variables of one scope, plus the scope it is nested in.
+///
+/// Scopes are shared, so a block (and later a closure) can keep its parent alive and see
+/// assignments made through it.
+#[derive(Debug, Default)]
+pub struct Environment {
+    values: HashMap<String, Value>,
+    enclosing: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Environment {
+    /// A new outermost scope.
+    pub fn new() -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Self::default()))
+    }
+
+    /// A new scope nested in `enclosing`.
+    pub fn with_enclosing(enclosing: Rc<RefCell<Environment>>) -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Self {
+            values: HashMap::new(),
+            enclosing: Some(enclosing),
+        }))
+    }
+
+    /// A new scope with the same variables and parent as this one, so a loop can give each
+    /// iteration its own copy of the loop variables for closures to capture.
+    pub fn duplicate(&self) -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Self {
+            values: self.values.clone(),
+            enclosing: self.enclosing.clone(),
+        }))
+    }
+
+    /// Declares `name` in this scope, shadowing any variable with the same name in outer scopes.
+    pub fn define(&mut self, name: &str, value: Value) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    pub fn get(&self, name: &Token) -> Result<Value, RuntimeError> {
+        match (self.values.get(&name.lexeme), &self.enclosing) {
+            (Some(value), _) => Ok(value.clone()),
+            (None, Some(enclosing)) => enclosing.borrow().get(name),
+            (None, None) => Err(Self::undefined(name)),
+        }
+    }
+
+    /// Changes the innermost variable called `name`, which must already be declared.
+    pub fn assign(&mut self, name: &Token, value: Value) -> Result<(), RuntimeError> {
+        if let Some(slot) = self.values.get_mut(&name.lexeme) {
+            *slot = value;
+            return Ok(());
+        }
+
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow_mut().assign(name, value),
+            None => Err(Self::undefined(name)),
+        }
+    }
+
+    fn undefined(name: &Token) -> RuntimeError {
+        RuntimeError::at(RuntimeErrorKind::UndefinedVariable(name.lexeme.clone()), name)
+            .with_help(&format!("declare it first with `let {} = ...;`", name.lexeme))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::tokens::TokenKind;
+
+    fn identifier(name: &str) -> Token {
+        Token::dynamic_tokenkind("filename".to_string(), TokenKind::Identifier, name.to_string(), 1, 1).unwrap()
+    }
+
+    #[test]
+    fn define_and_get() {
+        let environment = Environment::new();
+        environment.borrow_mut().define("x", Value::Number(1.0));
+
+        assert_eq!(environment.borrow().get(&identifier("x")), Ok(Value::Number(1.0)));
+    }
+
+    #[test]
+    fn redefine_replaces() {
+        let environment = Environment::new();
+        environment.borrow_mut().define("x", Value::Number(1.0));
+        environment.borrow_mut().define("x", Value::String("one".to_string()));
+
+        assert_eq!(environment.borrow().get(&identifier("x")), Ok(Value::String("one".to_string())));
+    }
+
+    #[test]
+    fn undefined_variable() {
+        let environment = Environment::new();
+
+        let error = environment.borrow().get(&identifier("y")).unwrap_err();
+
+        assert_eq!(error.kind, RuntimeErrorKind::UndefinedVariable("y".to_string()));
+        assert_eq!(error.help, Some("declare it first with `let y = ...;`".to_string()));
+    }
+
+    #[test]
+    fn get_looks_through_enclosing_scopes() {
+        let global = Environment::new();
+        global.borrow_mut().define("x", Value::Number(1.0));
+        let inner = Environment::with_enclosing(Environment::with_enclosing(global));
+
+        assert_eq!(inner.borrow().get(&identifier("x")), Ok(Value::Number(1.0)));
+    }
+
+    #[test]
+    fn shadowing_leaves_outer_variable_alone() {
+        let global = Environment::new();
+        global.borrow_mut().define("x", Value::Number(1.0));
+        let inner = Environment::with_enclosing(global.clone());
+        inner.borrow_mut().define("x", Value::Number(2.0));
+
+        assert_eq!(inner.borrow().get(&identifier("x")), Ok(Value::Number(2.0)));
+        assert_eq!(global.borrow().get(&identifier("x")), Ok(Value::Number(1.0)));
+    }
+
+    #[test]
+    fn assign_changes_the_declaring_scope() {
+        let global = Environment::new();
+        global.borrow_mut().define("x", Value::Number(1.0));
+        let inner = Environment::with_enclosing(global.clone());
+
+        inner.borrow_mut().assign(&identifier("x"), Value::Number(5.0)).unwrap();
+
+        assert_eq!(global.borrow().get(&identifier("x")), Ok(Value::Number(5.0)));
+    }
+
+    #[test]
+    fn assign_to_undeclared_variable() {
+        let environment = Environment::new();
+
+        let error = environment.borrow_mut().assign(&identifier("z"), Value::Null).unwrap_err();
+
+        assert_eq!(error.kind, RuntimeErrorKind::UndefinedVariable("z".to_string()));
+    }
+}
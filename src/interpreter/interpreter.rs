@@ -0,0 +1,97 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{ast::{error::RuntimeError, value::Value}, stmt::stmt::Stmt};
+
+use super::{environment::Environment, natives};
+
+/// Stack size for the thread scripts run on, enough for `MAX_CALL_DEPTH` nested calls even in a
+/// debug build, so deep recursion ends in a runtime error instead of overflowing the real stack.
+pub const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+/// Runs parsed statements, keeping the global scope between runs so a program can be fed to it
+/// a piece at a time, like the REPL does with each line.
+pub struct Interpreter {
+    globals: Rc<RefCell<Environment>>,
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        let interpreter = Self {
+            globals: Environment::new(),
+        };
+        natives::define(&mut interpreter.globals().borrow_mut());
+
+        interpreter
+    }
+
+    /// Executes `statements` in order, stopping at the first error.
+    ///
+    /// Returns the value of the last statement when it is an expression statement, which is what
+    /// the REPL echoes back.
+    pub fn interpret(&mut self, statements: &[Stmt]) -> Result<Option<Value>, RuntimeError> {
+        let mut last = None;
+
+        for statement in statements {
+            last = match statement {
+                Stmt::Expression { expression, .. } => Some(expression.evaluate(&self.globals)?),
+                _ => {
+                    statement.execute(&self.globals)?;
+                    None
+                }
+            };
+        }
+
+        Ok(last)
+    }
+
+    pub fn globals(&self) -> &Rc<RefCell<Environment>> {
+        &self.globals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ast::error::RuntimeErrorKind, lexer::{lexer::Lexer, tokens::{Token, TokenKind}}, parser::parser::Parser};
+
+    fn run(interpreter: &mut Interpreter, source: &str) -> Result<Option<Value>, RuntimeError> {
+        let tokens = Lexer::new("filename", source.to_string()).tokenize().unwrap();
+        let stmts = Parser::new(tokens).parse().ok().unwrap();
+
+        interpreter.interpret(&stmts)
+    }
+
+    fn global(interpreter: &Interpreter, name: &str) -> Result<Value, RuntimeError> {
+        let name = Token::dynamic_tokenkind("filename".to_string(), TokenKind::Identifier, name.to_string(), 1, 1).unwrap();
+        interpreter.globals().borrow().get(&name)
+    }
+
+    #[test]
+    fn keeps_globals_between_runs() {
+        let mut interpreter = Interpreter::new();
+
+        run(&mut interpreter, "let x = 2;").unwrap();
+        run(&mut interpreter, "let y = x * 21;").unwrap();
+
+        assert_eq!(global(&interpreter, "y"), Ok(Value::Number(42.0)));
+    }
+
+    #[test]
+    fn returns_last_expression_value() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(run(&mut interpreter, "let x = 1; x + 1;"), Ok(Some(Value::Number(2.0))));
+        assert_eq!(run(&mut interpreter, "x + 1; let y = 2;"), Ok(None));
+    }
+
+    #[test]
+    fn stops_at_first_error() {
+        let mut interpreter = Interpreter::new();
+
+        let error = run(&mut interpreter, "let a = 1; let b = nope; let c = 3;").unwrap_err();
+
+        assert_eq!(error.kind, RuntimeErrorKind::UndefinedVariable("nope".to_string()));
+        assert_eq!(global(&interpreter, "a"), Ok(Value::Number(1.0)));
+        assert!(global(&interpreter, "c").is_err());
+    }
+}
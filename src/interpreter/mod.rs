@@ -0,0 +1,4 @@
+pub mod environment;
+#[allow(clippy::module_inception)]
+pub mod interpreter;
+mod natives;
@@ -0,0 +1,130 @@
+use std::rc::Rc;
+
+use crate::ast::{error::{RuntimeError, RuntimeErrorKind}, value::Value};
+
+use super::environment::Environment;
+
+/// Defines the functions every program starts with in `globals`.
+pub fn define(globals: &mut Environment) {
+    native(globals, "print", 1, |arguments| {
+        println!("{}", arguments[0]);
+        Ok(Value::Null)
+    });
+
+    native(globals, "len", 1, |arguments| match &arguments[0] {
+        Value::Array(arr) => Ok(Value::Number(arr.len() as f64)),
+        Value::String(s) => Ok(Value::Number(s.chars().count() as f64)),
+        Value::Object(fields) => Ok(Value::Number(fields.len() as f64)),
+        other => Err(invalid_argument("len", 1, "an array, a string or an object", other)),
+    });
+
+    // `map(a, (x) => x * 2)`, a new array with `f` applied to each element
+    native(globals, "map", 2, |arguments| {
+        let arr = array("map", &arguments[0])?;
+        let mapped = arr.iter()
+            .map(|element| call("map", &arguments[1], vec![element.clone()]))
+            .collect::<Result<Vec<Value>, RuntimeError>>()?;
+        Ok(Value::Array(mapped))
+    });
+
+    // `sort(a, (x, y) => x - y)`, a sorted copy of `a`, the comparator gives a negative number
+    // when its first argument goes first
+    native(globals, "sort", 2, |arguments| {
+        let mut sorted = array("sort", &arguments[0])?.clone();
+
+        // an insertion sort, stable, and a comparator that errors or contradicts itself cant
+        // break it like it can break `slice::sort_by`
+        for i in 1..sorted.len() {
+            let mut j = i;
+            while j > 0 {
+                let order = call("sort", &arguments[1], vec![sorted[j].clone(), sorted[j - 1].clone()])?;
+                match order.as_number() {
+                    Some(n) if n < 0.0 => sorted.swap(j, j - 1),
+                    Some(_) => break,
+                    None => return Err(RuntimeError::unlocated(RuntimeErrorKind::InvalidArgument {
+                        name: "sort".to_string(),
+                        position: 2,
+                        expected: "a comparator returning a number",
+                        found: order.type_name(),
+                    })),
+                }
+                j -= 1;
+            }
+        }
+        Ok(Value::Array(sorted))
+    });
+}
+
+fn native(globals: &mut Environment, name: &str, arity: usize, fun: impl Fn(&Vec<Value>) -> Result<Value, RuntimeError> + 'static) {
+    globals.define(name, Value::Callable { name: name.to_string(), arity, fun: Rc::new(fun) });
+}
+
+// calls the function a native was given, with the arity check `Expr::Call` does for the script
+fn call(native: &str, function: &Value, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+    match function {
+        Value::Callable { arity, fun, .. } if *arity == arguments.len() => fun(&arguments),
+        Value::Callable { name, arity, .. } => Err(RuntimeError::unlocated(RuntimeErrorKind::ArityMismatch {
+            name: name.clone(),
+            expected: *arity,
+            found: arguments.len(),
+        })),
+        other => Err(invalid_argument(native, 2, "a function", other)),
+    }
+}
+
+fn array<'a>(native: &str, value: &'a Value) -> Result<&'a Vec<Value>, RuntimeError> {
+    value.as_array().ok_or_else(|| invalid_argument(native, 1, "an array", value))
+}
+
+fn invalid_argument(native: &str, position: usize, expected: &'static str, found: &Value) -> RuntimeError {
+    RuntimeError::unlocated(RuntimeErrorKind::InvalidArgument { name: native.to_string(), position, expected, found: found.type_name() })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{interpreter::interpreter::Interpreter, lexer::lexer::Lexer, parser::parser::Parser};
+
+    use super::*;
+
+    fn run(source: &str) -> Result<Option<Value>, RuntimeError> {
+        let tokens = Lexer::new("filename", source.to_string()).tokenize().unwrap();
+        let stmts = Parser::new(tokens).parse().ok().unwrap();
+
+        Interpreter::new().interpret(&stmts)
+    }
+
+    fn rendered(source: &str) -> String {
+        run(source).unwrap().unwrap().to_string()
+    }
+
+    #[test]
+    fn map_and_sort_take_closures() {
+        assert_eq!(rendered("map([1, 2, 3], (x) => x * 2);"), "[2, 4, 6]");
+        assert_eq!(rendered("let k = 10; map([1, 2], fn (x) { return x + k; });"), "[11, 12]");
+        assert_eq!(rendered("sort([3, 1, 2], (a, b) => a - b);"), "[1, 2, 3]");
+        assert_eq!(rendered("sort([3, 1, 2], (a, b) => b - a);"), "[3, 2, 1]");
+        assert_eq!(rendered("let a = [2, 1]; sort(a, (x, y) => x - y); a;"), "[2, 1]");
+        assert_eq!(rendered("len(map([\"a\", \"bc\"], (s) => len(s)));"), "2");
+    }
+
+    #[test]
+    fn sort_is_stable() {
+        let source = "let people = [{ name: \"b\", age: 2 }, { name: \"a\", age: 1 }, { name: \"c\", age: 2 }];
+            map(sort(people, (p, q) => p.age - q.age), (p) => p.name);";
+
+        assert_eq!(rendered(source), "[a, b, c]");
+    }
+
+    #[test]
+    fn native_errors_point_at_the_call() {
+        let error = run("let x = 1;\nmap(x, (v) => v);").unwrap_err();
+        assert_eq!(error.message(), "Argument 1 of map must be an array, found Number");
+        assert_eq!(error.line_number, 2);
+
+        let error = run("map([1], (a, b) => a);").unwrap_err();
+        assert_eq!(error.message(), "Callable anonymous expected 2 arguments but got 1");
+
+        let error = run("sort([1, 2], (a, b) => true);").unwrap_err();
+        assert_eq!(error.code(), "R0102");
+    }
+}
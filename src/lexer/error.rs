@@ -0,0 +1,93 @@
+use crate::diagnostics::error::{Error, ErrorKind};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LexErrorKind {
+    UnexpectedCharacter(char),
+    InvalidToken(String), // a Token constructor refused what the lexer read
+
+    // Strings
+    UnterminatedString,
+    UnterminatedInterpolation,
+    UnfinishedEscape,
+    InvalidEscape(char),
+    ExpectedUnicodeBrace,
+    UnclosedUnicodeEscape(String), // digits read so far
+    UnicodeDigitCount,
+    InvalidUnicodeScalar(String),
+
+    // Numbers, each one with the number as written so far
+    LeadingDot,
+    MissingDigits(String),
+    InvalidDigit(char, String),
+    MisplacedSeparator(String),
+    MissingExponent(String),
+    UnexpectedInNumber(char, String),
+    NumberTooLarge(String),
+    InvalidNumber(String),
+}
+
+pub type LexError = Error<LexErrorKind>;
+
+impl ErrorKind for LexErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            LexErrorKind::UnexpectedCharacter(_) => "L0001",
+            LexErrorKind::InvalidToken(_) => "L0002",
+            LexErrorKind::UnterminatedString => "L0100",
+            LexErrorKind::UnterminatedInterpolation => "L0101",
+            LexErrorKind::UnfinishedEscape => "L0102",
+            LexErrorKind::InvalidEscape(_) => "L0103",
+            LexErrorKind::ExpectedUnicodeBrace => "L0104",
+            LexErrorKind::UnclosedUnicodeEscape(_) => "L0105",
+            LexErrorKind::UnicodeDigitCount => "L0106",
+            LexErrorKind::InvalidUnicodeScalar(_) => "L0107",
+            LexErrorKind::LeadingDot => "L0200",
+            LexErrorKind::MissingDigits(_) => "L0201",
+            LexErrorKind::InvalidDigit(_, _) => "L0202",
+            LexErrorKind::MisplacedSeparator(_) => "L0203",
+            LexErrorKind::MissingExponent(_) => "L0204",
+            LexErrorKind::UnexpectedInNumber(_, _) => "L0205",
+            LexErrorKind::NumberTooLarge(_) => "L0206",
+            LexErrorKind::InvalidNumber(_) => "L0207",
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            LexErrorKind::UnexpectedCharacter(c) => format!("Unexpected character '{}'", c),
+            LexErrorKind::InvalidToken(msg) => msg.clone(),
+            LexErrorKind::UnterminatedString => "Unterminated string starting".to_string(),
+            LexErrorKind::UnterminatedInterpolation => "Unterminated string interpolation starting".to_string(),
+            LexErrorKind::UnfinishedEscape => "Unfinished escape sequence".to_string(),
+            LexErrorKind::InvalidEscape(c) => format!("Invalid escape sequence '\\{}'", c),
+            LexErrorKind::ExpectedUnicodeBrace => "Expected '{' after '\\u'".to_string(),
+            LexErrorKind::UnclosedUnicodeEscape(digits) => format!("Expected '}}' to close '\\u{{{}'", digits),
+            LexErrorKind::UnicodeDigitCount => "Unicode escape must have 1 to 6 hex digits".to_string(),
+            LexErrorKind::InvalidUnicodeScalar(digits) => format!("Invalid unicode scalar value '{}'", digits),
+            LexErrorKind::LeadingDot => "Number literal cannot start with '.'".to_string(),
+            LexErrorKind::MissingDigits(raw) => format!("Expected digits after '{}'", raw),
+            LexErrorKind::InvalidDigit(c, raw) => format!("Invalid digit '{}' in number literal '{}'", c, raw),
+            LexErrorKind::MisplacedSeparator(raw) => format!("Misplaced '_' in number literal '{}'", raw),
+            LexErrorKind::MissingExponent(raw) => format!("Expected digits in the exponent of '{}'", raw),
+            LexErrorKind::UnexpectedInNumber(c, raw) => format!("Unexpected character '{}' in number literal '{}'", c, raw),
+            LexErrorKind::NumberTooLarge(raw) => format!("Number literal '{}' is too large", raw),
+            LexErrorKind::InvalidNumber(raw) => format!("Invalid number literal '{}'", raw),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::tokens::Span;
+
+    #[test]
+    fn test_display_and_code() {
+        let error = LexError::new(LexErrorKind::InvalidEscape('q'), Span::new(4, 6), 1, 5)
+            .with_help("valid escapes are \\n \\t \\r \\0 \\\\ \\\" \\$ and \\u{...}");
+
+        assert_eq!(error.to_string(), "Invalid escape sequence '\\q' in line 1 column 5");
+        assert_eq!(error.code(), "L0103");
+        assert!(error.help.is_some());
+    }
+}
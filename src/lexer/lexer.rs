@@ -1,5 +1,5 @@
 use std::{borrow::Borrow, collections::HashMap};
-use super::{tokens::{Token, TokenKind}, utils::StringStream};
+use super::{error::{LexError, LexErrorKind}, tokens::{Span, Token, TokenKind}, utils::StringStream};
 
 fn get_keywords_hashmap() -> HashMap<&'static str, TokenKind> {
     HashMap::from([
@@ -30,36 +30,40 @@ fn get_keywords_hashmap() -> HashMap<&'static str, TokenKind> {
 pub struct Lexer {
     source_filename: &'static str,
     contents: StringStream,
-    source: String,
     keywords: HashMap<&'static str, TokenKind>,
     position: (usize, usize),     // line, column //TODO
-    current_position: usize
+    current_position: usize,      // byte offset of the next character in the source
+    token_start: (usize, usize, usize), // line, column and byte offset where the token being scanned begins
+    interpolations: Vec<(usize, (usize, usize, usize))> // open braces inside each `${`, and where its string started
 }
 
 /*
-    position needs to be done right, because it doesnt arrange well the position, all of that is for error displaying on console
-    must be done, position must have the last index of the character
+    line and column are only for displaying errors on console, a token is at the column right after its last character
+    the exact location of every token is its span, the byte range it covers in the source
 */
 
 impl Lexer {
     pub fn new(source_filename: &'static str, source: String) -> Self {
         Lexer {
             source_filename,
-            contents: StringStream::new(source.clone()), // clone!
-            source,
+            contents: StringStream::new(source),
             keywords: get_keywords_hashmap(),
             position: (1, 1),
-            current_position: 0
+            current_position: 0,
+            token_start: (1, 1, 0),
+            interpolations: vec![]
         }
     }
 
-    pub fn tokenize(&mut self) -> Result<Vec<Token>, String> {
+    pub fn tokenize(&mut self) -> Result<Vec<Token>, Vec<LexError>> {
         let mut tokens = vec![];
         let mut errors = vec![];
-        while let Some(_) = self.contents.peek() {
+        while !self.contents.is_eof() {
+            self.token_start = (self.position.0, self.position.1, self.current_position);
             let token_result = self.scan_token();
             match token_result {
-                Ok(Some(token)) => {
+                Ok(Some(mut token)) => {
+                    token.span = Span::new(self.token_start.2, self.current_position);
                     tokens.push(token);
                 }
                 Ok(None) => {
@@ -70,101 +74,210 @@ impl Lexer {
                 }
             }
         }
+
+        if let Some((_, start)) = self.interpolations.pop() {
+            errors.push(self.error(LexErrorKind::UnterminatedInterpolation, start));
+        }
     
         // Add EOF token to the tokens vector
         tokens.push(Token::eof(
             String::from(self.source_filename),
             self.position.0,
             self.position.1 + 1,
-        ));
+        ).with_span(self.current_position, self.current_position));
     
-        // If there were errors, collect them and return as a single error string
         if !errors.is_empty() {
-            let joined = errors.join("\n");
-            return Err(joined);
+            return Err(errors);
         }
     
         Ok(tokens)
     }
     
-    fn scan_token(&mut self) -> Result<Option<Token>, String> {
+    fn scan_token(&mut self) -> Result<Option<Token>, LexError> {
         
         if let Some(char) = self.contents_next() {
 
             match char {
                 // Handle single-character static_tokenkinds
-                '(' => Token::static_tokenkind(
+                '(' => self.token(Token::static_tokenkind(
                     self.source_filename.to_string(),
                     TokenKind::LeftParen,
                     self.position.0,
                     self.position.1,
-                ).map(Some),
-                ')' => Token::static_tokenkind(
+                )),
+                ')' => self.token(Token::static_tokenkind(
                     self.source_filename.to_string(),
                     TokenKind::RightParen,
                     self.position.0,
                     self.position.1,
-                ).map(Some),
-                '{' => Token::static_tokenkind(
-                    self.source_filename.to_string(),
-                    TokenKind::LeftBrace,
-                    self.position.0,
-                    self.position.1,
-                ).map(Some),
-                '}' => Token::static_tokenkind(
-                    self.source_filename.to_string(),
-                    TokenKind::RightBrace,
-                    self.position.0,
-                    self.position.1,
-                ).map(Some),
-                ',' => Token::static_tokenkind(
+                )),
+                '{' => {
+                    if let Some((depth, _)) = self.interpolations.last_mut() {
+                        *depth += 1;
+                    }
+                    self.token(Token::static_tokenkind(
+                        self.source_filename.to_string(),
+                        TokenKind::LeftBrace,
+                        self.position.0,
+                        self.position.1,
+                    ))
+                },
+                '}' => {
+                    match self.interpolations.last_mut() {
+                        // closes the `${`, so the string it belongs to continues
+                        Some((0, start)) => {
+                            let start = *start;
+                            self.interpolations.pop();
+                            self.string(start, String::from('}'))
+                        },
+                        Some((depth, _)) => {
+                            *depth -= 1;
+                            self.token(Token::static_tokenkind(
+                                self.source_filename.to_string(),
+                                TokenKind::RightBrace,
+                                self.position.0,
+                                self.position.1,
+                            ))
+                        },
+                        None => self.token(Token::static_tokenkind(
+                            self.source_filename.to_string(),
+                            TokenKind::RightBrace,
+                            self.position.0,
+                            self.position.1,
+                        )),
+                    }
+                },
+                ',' => self.token(Token::static_tokenkind(
                     self.source_filename.to_string(),
                     TokenKind::Comma,
                     self.position.0,
                     self.position.1,
-                ).map(Some),
-                '.' => Token::static_tokenkind(
-                    self.source_filename.to_string(),
-                    TokenKind::Dot,
-                    self.position.0,
-                    self.position.1,
-                ).map(Some),
-                ';' => Token::static_tokenkind(
+                )),
+                '.' => {
+                    if self.contents.peek().is_some_and(|c| c.is_ascii_digit()) {
+                        self.skip_number_rest();
+                        return Err(self.error(LexErrorKind::LeadingDot, self.token_start)
+                            .with_help("add a leading zero, like `0.5`"));
+                    }
+                    self.token(Token::static_tokenkind(
+                        self.source_filename.to_string(),
+                        TokenKind::Dot,
+                        self.position.0,
+                        self.position.1,
+                    ))
+                },
+                ';' => self.token(Token::static_tokenkind(
                     self.source_filename.to_string(),
                     TokenKind::Semicolon,
                     self.position.0,
                     self.position.1,
-                ).map(Some),
-                '?' => Token::static_tokenkind(
-                    self.source_filename.to_string(),
-                    TokenKind::QuestionMark,
-                    self.position.0,
-                    self.position.1,
-                ).map(Some),
-                ':' => Token::static_tokenkind(
-                    self.source_filename.to_string(),
-                    TokenKind::Colon,
-                    self.position.0,
-                    self.position.1,
-                ).map(Some),
-                '+' => Token::static_tokenkind(
+                )),
+                '?' => {
+                    let kind = match self.contents.peek() {
+                        Some('?') => TokenKind::QuestionQuestion,
+                        Some('.') => TokenKind::QuestionDot,
+                        _ => TokenKind::QuestionMark,
+                    };
+                    if kind != TokenKind::QuestionMark {
+                        self.contents_next(); // consume the second character
+                    }
+                    self.token(Token::static_tokenkind(
+                        self.source_filename.to_string(),
+                        kind,
+                        self.position.0,
+                        self.position.1,
+                    ))
+                },
+                '[' => self.token(Token::static_tokenkind(
                     self.source_filename.to_string(),
-                    TokenKind::Plus,
+                    TokenKind::LeftBracket,
                     self.position.0,
                     self.position.1,
-                ).map(Some),
-                '-' => Token::static_tokenkind(
+                )),
+                ']' => self.token(Token::static_tokenkind(
                     self.source_filename.to_string(),
-                    TokenKind::Minus,
+                    TokenKind::RightBracket,
                     self.position.0,
                     self.position.1,
-                ).map(Some),
-                '*' => Token::static_tokenkind(
+                )),
+                ':' => self.token(Token::static_tokenkind(
                     self.source_filename.to_string(),
-                    TokenKind::Star,
+                    TokenKind::Colon,
                     self.position.0,
                     self.position.1,
-                ).map(Some),
+                )),
+                '+' => {
+                    if self.contents.peek() == Some(&'=') {
+                        self.contents_next(); // consume `=`
+                        self.token(Token::static_tokenkind(
+                            self.source_filename.to_string(),
+                            TokenKind::PlusEqual,
+                            self.position.0,
+                            self.position.1,
+                        ))
+                    } else {
+                        self.token(Token::static_tokenkind(
+                            self.source_filename.to_string(),
+                            TokenKind::Plus,
+                            self.position.0,
+                            self.position.1,
+                        ))
+                    }
+                },
+                '-' => {
+                    if self.contents.peek() == Some(&'=') {
+                        self.contents_next(); // consume `=`
+                        self.token(Token::static_tokenkind(
+                            self.source_filename.to_string(),
+                            TokenKind::MinusEqual,
+                            self.position.0,
+                            self.position.1,
+                        ))
+                    } else {
+                        self.token(Token::static_tokenkind(
+                            self.source_filename.to_string(),
+                            TokenKind::Minus,
+                            self.position.0,
+                            self.position.1,
+                        ))
+                    }
+                },
+                '*' => {
+                    if self.contents.peek() == Some(&'=') {
+                        self.contents_next(); // consume `=`
+                        self.token(Token::static_tokenkind(
+                            self.source_filename.to_string(),
+                            TokenKind::StarEqual,
+                            self.position.0,
+                            self.position.1,
+                        ))
+                    } else {
+                        self.token(Token::static_tokenkind(
+                            self.source_filename.to_string(),
+                            TokenKind::Star,
+                            self.position.0,
+                            self.position.1,
+                        ))
+                    }
+                },
+                '%' => {
+                    if self.contents.peek() == Some(&'=') {
+                        self.contents_next(); // consume `=`
+                        self.token(Token::static_tokenkind(
+                            self.source_filename.to_string(),
+                            TokenKind::PercentEqual,
+                            self.position.0,
+                            self.position.1,
+                        ))
+                    } else {
+                        self.token(Token::static_tokenkind(
+                            self.source_filename.to_string(),
+                            TokenKind::Percent,
+                            self.position.0,
+                            self.position.1,
+                        ))
+                    }
+                },
 
                 // Handle potential multi-line and one-line comments
                 '/' => {
@@ -177,14 +290,8 @@ impl Lexer {
 
                                 self.contents_next(); // Consumes `*`
                                 self.contents_next(); // Consumes `/`
-                                self.position.1 -= 1; // WHAT
                                 break;
 
-                            } else if c == '\n' {
-                                self.position.0 += 1;
-                                self.position.1 = 1;
-                                self.contents.next();
-
                             } else {
                                 self.contents_next();
                             }
@@ -199,8 +306,6 @@ impl Lexer {
                         while let Some(&c) = self.contents.peek() {
                             if c == '\n' {
                                 self.contents_next(); // Consume the newline
-                                self.position.0 += 1; // New line
-                                self.position.1 = 1;  // Reset column position
                                 break;
                             } else {
                                 self.contents_next(); // Consume the current character
@@ -208,14 +313,22 @@ impl Lexer {
                         }
 
                         Ok(None) // Return `None` because this is a comment
+                    } else if self.contents.peek() == Some(&'=') {
+                        self.contents_next(); // consume `=`
+                        self.token(Token::static_tokenkind(
+                            self.source_filename.to_string(),
+                            TokenKind::SlashEqual,
+                            self.position.0,
+                            self.position.1,
+                        ))
                     } else {
                         // Handle the `/` token (not part of a comment)
-                        Token::static_tokenkind(
+                        self.token(Token::static_tokenkind(
                             self.source_filename.to_string(),
                             TokenKind::Slash,
                             self.position.0,
                             self.position.1,
-                        ).map(Some)
+                        ))
                     }
                 },
     
@@ -223,98 +336,97 @@ impl Lexer {
                 '!' => {
                     if self.contents.peek() == Some(&'=') {
                         self.contents_next(); // Consume `=`
-                        Token::static_tokenkind(
+                        self.token(Token::static_tokenkind(
                             self.source_filename.to_string(),
                             TokenKind::BangEqual,
                             self.position.0,
                             self.position.1,
-                        ).map(Some)
+                        ))
                     } else {
-                        Token::static_tokenkind(
+                        self.token(Token::static_tokenkind(
                             self.source_filename.to_string(),
                             TokenKind::Bang,
                             self.position.0,
                             self.position.1,
-                        ).map(Some)
+                        ))
                     }
                 }
                 '=' => {
                     if self.contents.peek() == Some(&'=') {
                         self.contents_next(); // Consume `=`
-                        Token::static_tokenkind(
+                        self.token(Token::static_tokenkind(
                             self.source_filename.to_string(),
                             TokenKind::EqualEqual,
                             self.position.0,
                             self.position.1,
-                        ).map(Some)
+                        ))
+                    } else if self.contents.peek() == Some(&'>') {
+                        self.contents_next(); // Consume `>`
+                        self.token(Token::static_tokenkind(
+                            self.source_filename.to_string(),
+                            TokenKind::Arrow,
+                            self.position.0,
+                            self.position.1,
+                        ))
                     } else {
-                        Token::static_tokenkind(
+                        self.token(Token::static_tokenkind(
                             self.source_filename.to_string(),
                             TokenKind::Equal,
                             self.position.0,
                             self.position.1,
-                        ).map(Some)
+                        ))
                     }
                 }
 
                 '>' => {
                     if self.contents.peek() == Some(&'=') {
                         self.contents_next(); // consume `=`
-                        Token::static_tokenkind(
+                        self.token(Token::static_tokenkind(
                             self.source_filename.to_string(),
                             TokenKind::GreaterEqual,
                             self.position.0,
                             self.position.1
-                        ).map(Some)
+                        ))
                     } else {
-                        Token::static_tokenkind(
+                        self.token(Token::static_tokenkind(
                             self.source_filename.to_string(),
                             TokenKind::Greater,
                             self.position.0,
                             self.position.1,
-                        ).map(Some)
+                        ))
                     }
                 },
 
                 '<' => {
                     if self.contents.peek() == Some(&'=') {
                         self.contents_next(); // consume `=`
-                        Token::static_tokenkind(
+                        self.token(Token::static_tokenkind(
                             self.source_filename.to_string(),
                             TokenKind::LessEqual,
                             self.position.0,
                             self.position.1
-                        ).map(Some)
+                        ))
                     } else {
-                        Token::static_tokenkind(
+                        self.token(Token::static_tokenkind(
                             self.source_filename.to_string(),
                             TokenKind::Less,
                             self.position.0,
                             self.position.1,
-                        ).map(Some)
+                        ))
                     }
                 }
     
-                // Handle whitespace (advance position and ignore)
-                ' ' => {
-                    self.position.1 += 1;
-                    Ok(None)
-                }
+                // Handle whitespace (contents_next already moved the position)
+                ' ' | '\r' | '\n' => Ok(None),
                 
                 '\t' => {
-                    self.position.1 += 4; // tab = 4
-                    Ok(None)
-                }
-
-                '\r' => Ok(None),
-    
-                // Handle newlines (update line and reset column)
-                '\n' => {
-                    self.position.0 += 1;
-                    self.position.1 = 1;
+                    self.position.1 += 3; // tab = 4
                     Ok(None)
                 }
     
+                // Handle string literals, which may span multiple lines
+                '"' => self.string(self.token_start, String::from('"')),
+
                 // Handle identifiers and keywords
                 c if c.is_alphabetic() || c == '_' => {
                     let mut identifier = String::new();
@@ -330,57 +442,296 @@ impl Lexer {
                     }
     
                     if let Some(keyword_kind) = self.keywords.get(&identifier.borrow()) {
-                        Token::static_tokenkind(
+                        self.token(Token::static_tokenkind(
                             self.source_filename.to_string(),
                             (*keyword_kind).clone(), // clone
                             self.position.0,
                             self.position.1,
-                        ).map(Some)
+                        ))
                     } else { // if it is not a keyword its an identifier (name of either function or variable)
-                        Token::dynamic_tokenkind(
+                        self.token(Token::dynamic_tokenkind(
                             self.source_filename.to_string(),
                             TokenKind::Identifier,
                             identifier,
                             self.position.0,
                             self.position.1,
-                        ).map(Some)
+                        ))
                     }
                 }
     
                 // Handle numbers
-                c if c.is_digit(10) => {
-                    let mut number = String::new();
-                    number.push(c);
+                c if c.is_ascii_digit() => self.number(c),
     
-                    while let Some(&next_char) = self.contents.peek() {
-                        if next_char.is_digit(10) || next_char == '.' {
-                            number.push(next_char);
-                            self.contents_next();
-                        } else {
-                            break;
+                // Unknown character (error)
+                _ => Err(self.error(LexErrorKind::UnexpectedCharacter(char), self.token_start)),
+            }
+        } else {
+            Ok(None) // nothing left to scan
+        }
+    }
+
+    // error covering from `start` (line, column and byte offset) up to the current character
+    fn error(&self, kind: LexErrorKind, start: (usize, usize, usize)) -> LexError {
+        LexError::new(kind, Span::new(start.2, self.current_position), start.0, start.1)
+    }
+
+    // the Token constructors only fail for kinds the lexer never gives them, so their message is kept as is
+    fn token(&self, token: Result<Token, String>) -> Result<Option<Token>, LexError> {
+        token
+            .map(Some)
+            .map_err(|msg| self.error(LexErrorKind::InvalidToken(msg), self.token_start))
+    }
+
+    // scans a string up to its closing `"`, or up to a `${`, which gives an Interpolation token
+    // and leaves the lexer inside the interpolated expression until the matching `}`
+    fn string(&mut self, start: (usize, usize, usize), mut raw: String) -> Result<Option<Token>, LexError> {
+        let mut kind = TokenKind::String;
+        let mut value = String::new();
+        let mut error = None; // keep scanning up to the closing `"` so the rest of the string isnt lexed as code
+
+        loop {
+            match self.contents.peek() {
+                Some('"') => {
+                    raw.push('"');
+                    self.contents_next(); // Consume the closing `"`
+                    break;
+                }
+                Some('$') if self.contents.peek_next() == Some(&'{') => {
+                    raw.push_str("${");
+                    self.contents_next(); // Consume `$`
+                    self.contents_next(); // Consume `{`
+                    self.interpolations.push((0, start));
+                    kind = TokenKind::Interpolation;
+                    break;
+                }
+                Some('\\') => {
+                    match self.escape(&mut raw) {
+                        Ok(c) => value.push(c),
+                        Err(err) => {
+                            error.get_or_insert(err);
                         }
                     }
-    
-                    Token::dynamic_tokenkind(
-                        self.source_filename.to_string(),
-                        TokenKind::Number,
-                        number,
-                        self.position.0,
-                        self.position.1,
-                    ).map(Some)
                 }
-    
-                // Unknown character (error)
-                _ => Err(format!("Unexpected character: '{}'.", char)),
+                Some(&c) => {
+                    value.push(c);
+                    raw.push(c);
+                    self.contents_next();
+                }
+                None => return Err(self.error(LexErrorKind::UnterminatedString, start)),
+            }
+        }
+
+        if let Some(err) = error {
+            return Err(err);
+        }
+
+        let token = self.token(Token::dynamic_tokenkind(
+            self.source_filename.to_string(),
+            kind,
+            value,
+            self.position.0,
+            self.position.1,
+        ))?;
+        Ok(token.map(|mut token| {
+            token.lexeme = raw; // the lexeme keeps the escapes as written
+            token
+        }))
+    }
+
+    // decodes the escape sequence starting at the current `\`, pushing the source text into `raw`
+    fn escape(&mut self, raw: &mut String) -> Result<char, LexError> {
+        let start = (self.position.0, self.position.1, self.current_position);
+        self.contents_next(); // Consume `\`
+        raw.push('\\');
+
+        let c = match self.contents.peek() {
+            Some(&c) if c != '\n' => c,
+            _ => return Err(self.error(LexErrorKind::UnfinishedEscape, start)),
+        };
+        self.contents_next();
+        raw.push(c);
+
+        match c {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            '0' => Ok('\0'),
+            '\\' => Ok('\\'),
+            '"' => Ok('"'),
+            '$' => Ok('$'),
+            'u' => {
+                if self.contents.peek() != Some(&'{') {
+                    return Err(self.error(LexErrorKind::ExpectedUnicodeBrace, start)
+                        .with_help("write unicode escapes like `\\u{1F600}`"));
+                }
+                self.contents_next();
+                raw.push('{');
+
+                let mut digits = String::new();
+                while let Some(&d) = self.contents.peek() {
+                    if d.is_ascii_hexdigit() {
+                        digits.push(d);
+                        raw.push(d);
+                        self.contents_next();
+                    } else {
+                        break;
+                    }
+                }
+
+                if self.contents.peek() != Some(&'}') {
+                    return Err(self.error(LexErrorKind::UnclosedUnicodeEscape(digits), start));
+                }
+                self.contents_next();
+                raw.push('}');
+
+                if digits.is_empty() || digits.len() > 6 {
+                    return Err(self.error(LexErrorKind::UnicodeDigitCount, start));
+                }
+
+                u32::from_str_radix(&digits, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| self.error(LexErrorKind::InvalidUnicodeScalar(digits), start))
+            }
+            other => Err(self.error(LexErrorKind::InvalidEscape(other), start)
+                .with_help("valid escapes are \\n \\t \\r \\0 \\\\ \\\" \\$ and \\u{...}")),
+        }
+    }
+
+    // decimal numbers may have a fraction and an exponent, `0x`, `0b` and `0o` prefixes give integers,
+    // and `_` can separate digits anywhere but at the edges
+    fn number(&mut self, first: char) -> Result<Option<Token>, LexError> {
+        let start = self.token_start;
+        let mut raw = String::from(first);
+
+        let radix = match (first, self.contents.peek()) {
+            ('0', Some('x')) => 16,
+            ('0', Some('b')) => 2,
+            ('0', Some('o')) => 8,
+            _ => 10,
+        };
+
+        let value = if radix != 10 {
+            raw.push(self.contents_next().unwrap()); // Consume the prefix letter
+            let digits = self.digits(&mut raw, |c| c.is_ascii_alphanumeric());
+
+            if digits.is_empty() {
+                return Err(self.error(LexErrorKind::MissingDigits(raw), start));
+            }
+            if let Some(bad) = digits.chars().find(|c| *c != '_' && !c.is_digit(radix)) {
+                return Err(self.error(LexErrorKind::InvalidDigit(bad, raw), start));
+            }
+            if !Self::valid_separators(&digits) {
+                return Err(self.error(LexErrorKind::MisplacedSeparator(raw), start)
+                    .with_help("`_` can only go between two digits"));
+            }
+
+            match u64::from_str_radix(&digits.replace('_', ""), radix) {
+                Ok(n) => n as f64,
+                Err(_) => return Err(self.error(LexErrorKind::NumberTooLarge(raw), start)),
             }
         } else {
-            Err("Unexpected end of input.".to_string())
+            let integer = format!("{}{}", first, self.digits(&mut raw, |c| c.is_ascii_digit()));
+            let mut parts = vec![integer];
+
+            // a fraction needs a digit after the `.`, otherwise the `.` is left for member access
+            if self.contents.peek() == Some(&'.') && self.contents.peek_next().is_some_and(|c| c.is_ascii_digit()) {
+                raw.push('.');
+                self.contents_next();
+                parts.push(self.digits(&mut raw, |c| c.is_ascii_digit()));
+            }
+
+            if matches!(self.contents.peek(), Some('e') | Some('E')) {
+                raw.push(self.contents_next().unwrap());
+
+                if matches!(self.contents.peek(), Some('+') | Some('-')) {
+                    raw.push(self.contents_next().unwrap());
+                }
+
+                let exponent = self.digits(&mut raw, |c| c.is_ascii_digit());
+                if exponent.is_empty() {
+                    self.skip_number_rest();
+                    return Err(self.error(LexErrorKind::MissingExponent(raw), start));
+                }
+                parts.push(exponent);
+            }
+
+            let second_fraction = self.contents.peek() == Some(&'.')
+                && self.contents.peek_next().is_some_and(|c| c.is_ascii_digit());
+
+            if let Some(&c) = self.contents.peek().filter(|c| c.is_alphanumeric() || second_fraction) {
+                self.skip_number_rest();
+                return Err(self.error(LexErrorKind::UnexpectedInNumber(c, raw), start));
+            }
+            if !parts.iter().all(|part| Self::valid_separators(part)) {
+                return Err(self.error(LexErrorKind::MisplacedSeparator(raw), start)
+                    .with_help("`_` can only go between two digits"));
+            }
+
+            match raw.replace('_', "").parse::<f64>() {
+                Ok(n) => n,
+                Err(_) => return Err(self.error(LexErrorKind::InvalidNumber(raw), start)),
+            }
+        };
+
+        let token = self.token(Token::dynamic_tokenkind(
+            self.source_filename.to_string(),
+            TokenKind::Number,
+            value.to_string(),
+            self.position.0,
+            self.position.1,
+        ))?;
+        Ok(token.map(|mut token| {
+            token.lexeme = raw; // the lexeme keeps the number as written
+            token
+        }))
+    }
+
+    // consumes digits accepted by `is_digit` and `_` separators, returning them
+    fn digits(&mut self, raw: &mut String, is_digit: fn(&char) -> bool) -> String {
+        let mut digits = String::new();
+
+        while let Some(&c) = self.contents.peek() {
+            if is_digit(&c) || c == '_' {
+                digits.push(c);
+                raw.push(c);
+                self.contents_next();
+            } else {
+                break;
+            }
         }
+
+        digits
+    }
+
+    fn valid_separators(digits: &str) -> bool {
+        !digits.starts_with('_') && !digits.ends_with('_') && !digits.contains("__")
     }
 
+    // after a malformed number, skips what is left of it so it doesnt show up as more errors
+    fn skip_number_rest(&mut self) {
+        while let Some(&c) = self.contents.peek() {
+            if c.is_alphanumeric() || c == '_' || c == '.' {
+                self.contents_next();
+            } else {
+                break;
+            }
+        }
+    }
+
+    // every character is consumed through here, so the line, column and byte offset stay in sync
     fn contents_next(&mut self) -> Option<char> {
-        self.position.1 += 1;
-        self.contents.next()
+        let c = self.contents.next()?;
+
+        self.current_position += c.len_utf8();
+        if c == '\n' {
+            self.position.0 += 1;
+            self.position.1 = 1;
+        } else {
+            self.position.1 += 1;
+        }
+
+        Some(c)
     }
 
 }
@@ -389,6 +740,7 @@ impl Lexer {
 mod tests {
 
     use super::*;
+    use crate::ast::value::Value;
 
     #[test]
     fn contents_next() {
@@ -430,11 +782,11 @@ mod tests {
         let tokens = lexer.tokenize().unwrap();
 
         let token_test = vec![
-            Token::static_tokenkind("filename".to_string(), TokenKind::LeftParen, 1, 2).unwrap(),
-            Token::static_tokenkind("filename".to_string(), TokenKind::RightParen, 1, 3).unwrap(),
-            Token::static_tokenkind("filename".to_string(), TokenKind::LeftBrace, 1, 4).unwrap(),
-            Token::static_tokenkind("filename".to_string(), TokenKind::RightBrace, 1, 5).unwrap(),
-            Token::eof("filename".to_string(), 1, 6)
+            Token::static_tokenkind("filename".to_string(), TokenKind::LeftParen, 1, 2).unwrap().with_span(0, 1),
+            Token::static_tokenkind("filename".to_string(), TokenKind::RightParen, 1, 3).unwrap().with_span(1, 2),
+            Token::static_tokenkind("filename".to_string(), TokenKind::LeftBrace, 1, 4).unwrap().with_span(2, 3),
+            Token::static_tokenkind("filename".to_string(), TokenKind::RightBrace, 1, 5).unwrap().with_span(3, 4),
+            Token::eof("filename".to_string(), 1, 6).with_span(4, 4)
         ];
 
         assert_eq!(tokens, token_test);
@@ -442,22 +794,23 @@ mod tests {
 
     #[test]
     fn scan_symbols_and_operators() {
-        let source = ",.-+;*/?:".to_string();
+        let source = ",.-+;*/?:%".to_string();
         let mut lexer = Lexer::new("filename", source);
 
         let tokens = lexer.tokenize().unwrap();
 
         let token_test = vec![
-            Token::static_tokenkind("filename".to_string(), TokenKind::Comma, 1, 2).unwrap(),
-            Token::static_tokenkind("filename".to_string(), TokenKind::Dot, 1, 3).unwrap(),
-            Token::static_tokenkind("filename".to_string(), TokenKind::Minus, 1, 4).unwrap(),
-            Token::static_tokenkind("filename".to_string(), TokenKind::Plus, 1, 5).unwrap(),
-            Token::static_tokenkind("filename".to_string(), TokenKind::Semicolon, 1, 6).unwrap(),
-            Token::static_tokenkind("filename".to_string(), TokenKind::Star, 1, 7).unwrap(),
-            Token::static_tokenkind("filename".to_string(), TokenKind::Slash, 1, 8).unwrap(),
-            Token::static_tokenkind("filename".to_string(), TokenKind::QuestionMark, 1, 9).unwrap(),
-            Token::static_tokenkind("filename".to_string(), TokenKind::Colon, 1, 10).unwrap(),
-            Token::eof("filename".to_string(), 1, 11)
+            Token::static_tokenkind("filename".to_string(), TokenKind::Comma, 1, 2).unwrap().with_span(0, 1),
+            Token::static_tokenkind("filename".to_string(), TokenKind::Dot, 1, 3).unwrap().with_span(1, 2),
+            Token::static_tokenkind("filename".to_string(), TokenKind::Minus, 1, 4).unwrap().with_span(2, 3),
+            Token::static_tokenkind("filename".to_string(), TokenKind::Plus, 1, 5).unwrap().with_span(3, 4),
+            Token::static_tokenkind("filename".to_string(), TokenKind::Semicolon, 1, 6).unwrap().with_span(4, 5),
+            Token::static_tokenkind("filename".to_string(), TokenKind::Star, 1, 7).unwrap().with_span(5, 6),
+            Token::static_tokenkind("filename".to_string(), TokenKind::Slash, 1, 8).unwrap().with_span(6, 7),
+            Token::static_tokenkind("filename".to_string(), TokenKind::QuestionMark, 1, 9).unwrap().with_span(7, 8),
+            Token::static_tokenkind("filename".to_string(), TokenKind::Colon, 1, 10).unwrap().with_span(8, 9),
+            Token::static_tokenkind("filename".to_string(), TokenKind::Percent, 1, 11).unwrap().with_span(9, 10),
+            Token::eof("filename".to_string(), 1, 12).with_span(10, 10)
         ];
 
         assert_eq!(tokens, token_test);
@@ -471,7 +824,7 @@ mod tests {
         let tokens = lexer.tokenize().unwrap();
 
         let token_test = vec![
-            Token::eof("filename".to_string(), 1, 18)
+            Token::eof("filename".to_string(), 1, 18).with_span(16, 16)
         ];
 
         assert_eq!(tokens, token_test);
@@ -484,7 +837,7 @@ mod tests {
         let tokens = lexer.tokenize().unwrap();
 
         let token_test = vec![
-            Token::eof("filename".to_string(), 1, 5)
+            Token::eof("filename".to_string(), 1, 6).with_span(4, 4)
         ];
 
         assert_eq!(tokens, token_test);
@@ -498,7 +851,7 @@ mod tests {
         let tokens = lexer.tokenize().unwrap();
 
         let token_test = vec![
-            Token::eof("filename".to_string(), 1, 9)
+            Token::eof("filename".to_string(), 1, 10).with_span(8, 8)
         ];
 
         assert_eq!(tokens, token_test);
@@ -512,64 +865,335 @@ mod tests {
         let tokens = lexer.tokenize().unwrap();
 
         let token_test = vec![
-            Token::eof("filename".to_string(), 2, 3)
+            Token::eof("filename".to_string(), 2, 4).with_span(5, 5)
         ];
 
         assert_eq!(tokens, token_test);
         assert_eq!(lexer.contents.peek(), None);
     }
 
-    // #[test]
-    // fn operators_one_char() {
-    //     let source = "> < ! =".to_string();
-    //     let mut lexer = Lexer::new("filename", source);
-    //     let tokens = lexer.tokenize().unwrap();
-
-    //     let token_test = vec![
-    //         Token::static_tokenkind("filename".to_string(), TokenKind::Greater, 1, 2).unwrap(),
-    //         Token::static_tokenkind("filename".to_string(), TokenKind::Less, 1, 4).unwrap(),
-    //         Token::static_tokenkind("filename".to_string(), TokenKind::Bang, 1, 6).unwrap(),
-    //         Token::static_tokenkind("filename".to_string(), TokenKind::Equal, 1, 8).unwrap(),
-    //         Token::eof("filename".to_string(), 1, 9)
-    //     ];
-
-    //     assert_eq!(tokens, token_test);
-    //     assert_eq!(lexer.contents.peek(), None);
-    // }
-
-    // #[test]
-    // fn operators_two_char() {
-    //     let source = ">= <= != ==".to_string();
-    //     let mut lexer = Lexer::new("filename", source);
-    //     let tokens = lexer.tokenize().unwrap();
-
-    //     let token_test = vec![
-    //         Token::static_tokenkind("filename".to_string(), TokenKind::GreaterEqual, 1, 3).unwrap(),
-    //         Token::static_tokenkind("filename".to_string(), TokenKind::LessEqual, 1, 6).unwrap(),
-    //         Token::static_tokenkind("filename".to_string(), TokenKind::BangEqual, 1, 9).unwrap(),
-    //         Token::static_tokenkind("filename".to_string(), TokenKind::EqualEqual, 1, 12).unwrap(),
-    //         Token::eof("filename".to_string(), 1, 13)
-    //     ];
-
-    //     assert_eq!(tokens, token_test);
-    //     assert_eq!(lexer.contents.peek(), None);
-    // }
-
-    // #[test]
-    // fn operators_two_char_other_order() {
-    //     let source = "<= >= != ==".to_string();
-    //     let mut lexer = Lexer::new("filename", source);
-    //     let tokens = lexer.tokenize().unwrap();
-
-    //     let token_test = vec![
-    //         Token::static_tokenkind("filename".to_string(), TokenKind::LessEqual, 1, 3).unwrap(),
-    //         Token::static_tokenkind("filename".to_string(), TokenKind::GreaterEqual, 1, 6).unwrap(),
-    //         Token::static_tokenkind("filename".to_string(), TokenKind::BangEqual, 1, 9).unwrap(),
-    //         Token::static_tokenkind("filename".to_string(), TokenKind::EqualEqual, 1, 12).unwrap(),
-    //         Token::eof("filename".to_string(), 1, 13)
-    //     ];
-
-    //     assert_eq!(tokens, token_test);
-    //     assert_eq!(lexer.contents.peek(), None);
-    //}
-}
\ No newline at end of file
+    #[test]
+    fn scan_string() {
+        let mut lexer = Lexer::new("filename", "\"hello\"".to_string());
+
+        let tokens = lexer.tokenize().unwrap();
+
+        let mut string = Token::dynamic_tokenkind("filename".to_string(), TokenKind::String, "hello".to_string(), 1, 8).unwrap().with_span(0, 7);
+        string.lexeme = "\"hello\"".to_string();
+
+        let token_test = vec![
+            string,
+            Token::eof("filename".to_string(), 1, 9).with_span(7, 7)
+        ];
+
+        assert_eq!(tokens, token_test);
+    }
+
+    #[test]
+    fn scan_multi_line_string() {
+        let mut lexer = Lexer::new("filename", "\"a\nbc\"".to_string());
+
+        let tokens = lexer.tokenize().unwrap();
+
+        let mut string = Token::dynamic_tokenkind("filename".to_string(), TokenKind::String, "a\nbc".to_string(), 2, 4).unwrap().with_span(0, 6);
+        string.lexeme = "\"a\nbc\"".to_string();
+
+        let token_test = vec![
+            string,
+            Token::eof("filename".to_string(), 2, 5).with_span(6, 6)
+        ];
+
+        assert_eq!(tokens, token_test);
+    }
+
+    #[test]
+    fn unterminated_string() {
+        let mut lexer = Lexer::new("filename", "let\n\"open\nstring".to_string());
+
+        let errors = lexer.tokenize().unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, LexErrorKind::UnterminatedString);
+        assert_eq!(errors[0].span, Span::new(4, 16));
+        assert_eq!(errors[0].to_string(), "Unterminated string starting in line 2 column 1");
+    }
+
+    #[test]
+    fn scan_string_escapes() {
+        let mut lexer = Lexer::new("filename", r#""a\n\t\\\"\0""#.to_string());
+
+        let tokens = lexer.tokenize().unwrap();
+
+        assert_eq!(tokens[0].lexeme, r#""a\n\t\\\"\0""#);
+        assert_eq!(tokens[0].literal, Some(Value::String("a\n\t\\\"\0".to_string())));
+    }
+
+    #[test]
+    fn scan_string_unicode_escape() {
+        let mut lexer = Lexer::new("filename", r#""hi \u{1F600}""#.to_string());
+
+        let tokens = lexer.tokenize().unwrap();
+
+        assert_eq!(tokens[0].lexeme, r#""hi \u{1F600}""#);
+        assert_eq!(tokens[0].literal, Some(Value::String("hi \u{1F600}".to_string())));
+    }
+
+    #[test]
+    fn invalid_escape_points_at_backslash() {
+        let mut lexer = Lexer::new("filename", r#""abc\q" x"#.to_string());
+
+        let errors = lexer.tokenize().unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, LexErrorKind::InvalidEscape('q'));
+        assert_eq!(errors[0].span, Span::new(4, 6));
+        assert_eq!(errors[0].to_string(), "Invalid escape sequence '\\q' in line 1 column 5");
+    }
+
+    #[test]
+    fn invalid_unicode_escape() {
+        let mut lexer = Lexer::new("filename", r#""\u{D800}""#.to_string());
+
+        let errors = lexer.tokenize().unwrap_err();
+
+        assert_eq!(errors[0].to_string(), "Invalid unicode scalar value 'D800' in line 1 column 2");
+    }
+
+    #[test]
+    fn scan_string_interpolation() {
+        let mut lexer = Lexer::new("filename", r#""hello ${user}!""#.to_string());
+
+        let tokens = lexer.tokenize().unwrap();
+        let kinds: Vec<TokenKind> = tokens.iter().map(|t| t.kind.clone()).collect();
+
+        assert_eq!(kinds, vec![TokenKind::Interpolation, TokenKind::Identifier, TokenKind::String, TokenKind::EOF]);
+        assert_eq!(tokens[0].lexeme, r#""hello ${"#);
+        assert_eq!(tokens[0].literal, Some(Value::String("hello ".to_string())));
+        assert_eq!(tokens[2].lexeme, r#"}!""#);
+        assert_eq!(tokens[2].literal, Some(Value::String("!".to_string())));
+    }
+
+    #[test]
+    fn scan_nested_interpolation() {
+        let mut lexer = Lexer::new("filename", r#""a ${ {}("b ${c}") } d""#.to_string());
+
+        let tokens = lexer.tokenize().unwrap();
+        let kinds: Vec<TokenKind> = tokens.iter().map(|t| t.kind.clone()).collect();
+
+        assert_eq!(kinds, vec![
+            TokenKind::Interpolation, TokenKind::LeftBrace, TokenKind::RightBrace, TokenKind::LeftParen,
+            TokenKind::Interpolation, TokenKind::Identifier, TokenKind::String,
+            TokenKind::RightParen, TokenKind::String, TokenKind::EOF
+        ]);
+        assert_eq!(tokens[8].literal, Some(Value::String(" d".to_string())));
+    }
+
+    #[test]
+    fn escaped_interpolation() {
+        let mut lexer = Lexer::new("filename", r#""\${x}""#.to_string());
+
+        let tokens = lexer.tokenize().unwrap();
+
+        assert_eq!(tokens[0].kind, TokenKind::String);
+        assert_eq!(tokens[0].literal, Some(Value::String("${x}".to_string())));
+    }
+
+    #[test]
+    fn unterminated_interpolation() {
+        let mut lexer = Lexer::new("filename", r#""a ${b"#.to_string());
+
+        let errors = lexer.tokenize().unwrap_err();
+
+        assert_eq!(errors[0].kind, LexErrorKind::UnterminatedInterpolation);
+        assert_eq!(errors[0].to_string(), "Unterminated string interpolation starting in line 1 column 1");
+    }
+
+    fn number_literal(source: &str) -> Result<Option<Value>, String> {
+        let mut lexer = Lexer::new("filename", source.to_string());
+        lexer.tokenize()
+            .map(|tokens| tokens[0].literal.clone())
+            .map_err(|errors| errors[0].to_string())
+    }
+
+    #[test]
+    fn scan_numbers() {
+        assert_eq!(number_literal("42"), Ok(Some(Value::Number(42.0))));
+        assert_eq!(number_literal("3.25"), Ok(Some(Value::Number(3.25))));
+        assert_eq!(number_literal("1e-9"), Ok(Some(Value::Number(1e-9))));
+        assert_eq!(number_literal("2.5E+3"), Ok(Some(Value::Number(2500.0))));
+        assert_eq!(number_literal("1_000_000"), Ok(Some(Value::Number(1_000_000.0))));
+        assert_eq!(number_literal("0xFF"), Ok(Some(Value::Number(255.0))));
+        assert_eq!(number_literal("0b1010"), Ok(Some(Value::Number(10.0))));
+        assert_eq!(number_literal("0o17"), Ok(Some(Value::Number(15.0))));
+        assert_eq!(number_literal("0xdead_beef"), Ok(Some(Value::Number(3735928559.0))));
+    }
+
+    #[test]
+    fn number_keeps_raw_lexeme() {
+        let mut lexer = Lexer::new("filename", "0x1_F".to_string());
+
+        let tokens = lexer.tokenize().unwrap();
+
+        assert_eq!(tokens[0].lexeme, "0x1_F");
+        assert_eq!(tokens[0].literal, Some(Value::Number(31.0)));
+    }
+
+    #[test]
+    fn number_followed_by_dot() {
+        let mut lexer = Lexer::new("filename", "1.x".to_string());
+
+        let tokens = lexer.tokenize().unwrap();
+        let kinds: Vec<TokenKind> = tokens.iter().map(|t| t.kind.clone()).collect();
+
+        assert_eq!(kinds, vec![TokenKind::Number, TokenKind::Dot, TokenKind::Identifier, TokenKind::EOF]);
+    }
+
+    #[test]
+    fn malformed_numbers() {
+        assert_eq!(number_literal("1.2.3"), Err("Unexpected character '.' in number literal '1.2' in line 1 column 1".to_string()));
+        assert_eq!(number_literal(".5"), Err("Number literal cannot start with '.' in line 1 column 1".to_string()));
+        assert_eq!(number_literal("0x"), Err("Expected digits after '0x' in line 1 column 1".to_string()));
+        assert_eq!(number_literal("0b102"), Err("Invalid digit '2' in number literal '0b102' in line 1 column 1".to_string()));
+        assert_eq!(number_literal("1e"), Err("Expected digits in the exponent of '1e' in line 1 column 1".to_string()));
+        assert_eq!(number_literal("1__0"), Err("Misplaced '_' in number literal '1__0' in line 1 column 1".to_string()));
+        assert_eq!(number_literal("1_"), Err("Misplaced '_' in number literal '1_' in line 1 column 1".to_string()));
+        assert_eq!(number_literal("12abc"), Err("Unexpected character 'a' in number literal '12' in line 1 column 1".to_string()));
+    }
+
+    #[test]
+    fn unexpected_character() {
+        let mut lexer = Lexer::new("filename", "a @ b # c".to_string());
+
+        let errors = lexer.tokenize().unwrap_err();
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].kind, LexErrorKind::UnexpectedCharacter('@'));
+        assert_eq!(errors[0].span, Span::new(2, 3));
+        assert_eq!(errors[0].code(), "L0001");
+        assert_eq!(errors[1].to_string(), "Unexpected character '#' in line 1 column 7");
+    }
+
+    #[test]
+    fn leading_dot_has_help() {
+        let mut lexer = Lexer::new("filename", ".5".to_string());
+
+        let errors = lexer.tokenize().unwrap_err();
+
+        assert_eq!(errors[0].kind, LexErrorKind::LeadingDot);
+        assert_eq!(errors[0].help, Some("add a leading zero, like `0.5`".to_string()));
+    }
+
+    #[test]
+    fn spans_are_byte_offsets() {
+        let mut lexer = Lexer::new("filename", "let é = \"ñu\";\n  x".to_string());
+
+        let tokens = lexer.tokenize().unwrap();
+        let spans: Vec<Span> = tokens.iter().map(|t| t.span).collect();
+
+        assert_eq!(spans, vec![
+            Span::new(0, 3),
+            Span::new(4, 6),
+            Span::new(7, 8),
+            Span::new(9, 14),
+            Span::new(14, 15),
+            Span::new(18, 19),
+            Span::new(19, 19)
+        ]);
+    }
+
+    #[test]
+    fn operators_one_char() {
+        let source = "> < ! =".to_string();
+        let mut lexer = Lexer::new("filename", source);
+        let tokens = lexer.tokenize().unwrap();
+
+        let token_test = vec![
+            Token::static_tokenkind("filename".to_string(), TokenKind::Greater, 1, 2).unwrap().with_span(0, 1),
+            Token::static_tokenkind("filename".to_string(), TokenKind::Less, 1, 4).unwrap().with_span(2, 3),
+            Token::static_tokenkind("filename".to_string(), TokenKind::Bang, 1, 6).unwrap().with_span(4, 5),
+            Token::static_tokenkind("filename".to_string(), TokenKind::Equal, 1, 8).unwrap().with_span(6, 7),
+            Token::eof("filename".to_string(), 1, 9).with_span(7, 7)
+        ];
+
+        assert_eq!(tokens, token_test);
+        assert_eq!(lexer.contents.peek(), None);
+    }
+
+    #[test]
+    fn operators_two_char() {
+        let source = ">= <= != ==".to_string();
+        let mut lexer = Lexer::new("filename", source);
+        let tokens = lexer.tokenize().unwrap();
+
+        let token_test = vec![
+            Token::static_tokenkind("filename".to_string(), TokenKind::GreaterEqual, 1, 3).unwrap().with_span(0, 2),
+            Token::static_tokenkind("filename".to_string(), TokenKind::LessEqual, 1, 6).unwrap().with_span(3, 5),
+            Token::static_tokenkind("filename".to_string(), TokenKind::BangEqual, 1, 9).unwrap().with_span(6, 8),
+            Token::static_tokenkind("filename".to_string(), TokenKind::EqualEqual, 1, 12).unwrap().with_span(9, 11),
+            Token::eof("filename".to_string(), 1, 13).with_span(11, 11)
+        ];
+
+        assert_eq!(tokens, token_test);
+        assert_eq!(lexer.contents.peek(), None);
+    }
+
+    #[test]
+    fn operators_two_char_other_order() {
+        let source = "<= >= != ==".to_string();
+        let mut lexer = Lexer::new("filename", source);
+        let tokens = lexer.tokenize().unwrap();
+
+        let token_test = vec![
+            Token::static_tokenkind("filename".to_string(), TokenKind::LessEqual, 1, 3).unwrap().with_span(0, 2),
+            Token::static_tokenkind("filename".to_string(), TokenKind::GreaterEqual, 1, 6).unwrap().with_span(3, 5),
+            Token::static_tokenkind("filename".to_string(), TokenKind::BangEqual, 1, 9).unwrap().with_span(6, 8),
+            Token::static_tokenkind("filename".to_string(), TokenKind::EqualEqual, 1, 12).unwrap().with_span(9, 11),
+            Token::eof("filename".to_string(), 1, 13).with_span(11, 11)
+        ];
+
+        assert_eq!(tokens, token_test);
+        assert_eq!(lexer.contents.peek(), None);
+    }
+
+    #[test]
+    fn compound_assignment_operators() {
+        let source = "+= -= *= /= %= = =>".to_string();
+        let mut lexer = Lexer::new("filename", source);
+        let tokens = lexer.tokenize().unwrap();
+
+        let token_test = vec![
+            Token::static_tokenkind("filename".to_string(), TokenKind::PlusEqual, 1, 3).unwrap().with_span(0, 2),
+            Token::static_tokenkind("filename".to_string(), TokenKind::MinusEqual, 1, 6).unwrap().with_span(3, 5),
+            Token::static_tokenkind("filename".to_string(), TokenKind::StarEqual, 1, 9).unwrap().with_span(6, 8),
+            Token::static_tokenkind("filename".to_string(), TokenKind::SlashEqual, 1, 12).unwrap().with_span(9, 11),
+            Token::static_tokenkind("filename".to_string(), TokenKind::PercentEqual, 1, 15).unwrap().with_span(12, 14),
+            Token::static_tokenkind("filename".to_string(), TokenKind::Equal, 1, 17).unwrap().with_span(15, 16),
+            Token::static_tokenkind("filename".to_string(), TokenKind::Arrow, 1, 20).unwrap().with_span(17, 19),
+            Token::eof("filename".to_string(), 1, 21).with_span(19, 19)
+        ];
+
+        assert_eq!(tokens, token_test);
+    }
+
+    #[test]
+    fn null_safe_operators() {
+        let source = "a ?? b?.c ? d".to_string();
+        let mut lexer = Lexer::new("filename", source);
+        let kinds: Vec<TokenKind> = lexer.tokenize().unwrap().into_iter().map(|token| token.kind).collect();
+
+        assert_eq!(kinds, vec![
+            TokenKind::Identifier, TokenKind::QuestionQuestion, TokenKind::Identifier, TokenKind::QuestionDot,
+            TokenKind::Identifier, TokenKind::QuestionMark, TokenKind::Identifier, TokenKind::EOF
+        ]);
+    }
+
+    #[test]
+    fn brackets() {
+        let source = "a[0]".to_string();
+        let mut lexer = Lexer::new("filename", source);
+        let tokens = lexer.tokenize().unwrap();
+
+        assert_eq!(tokens[1], Token::static_tokenkind("filename".to_string(), TokenKind::LeftBracket, 1, 3).unwrap().with_span(1, 2));
+        assert_eq!(tokens[3], Token::static_tokenkind("filename".to_string(), TokenKind::RightBracket, 1, 5).unwrap().with_span(3, 4));
+    }
+}
@@ -1,3 +1,5 @@
+#[allow(clippy::module_inception)]
 pub mod lexer;
 pub mod utils;
-pub mod tokens;
\ No newline at end of file
+pub mod tokens;
+pub mod error;
\ No newline at end of file
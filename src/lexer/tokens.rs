@@ -3,20 +3,23 @@ use std::collections::HashMap;
 use crate::ast::value::Value;
 
 #[derive(Clone, PartialEq, Debug)] // DONT USE IT AAA
+#[allow(dead_code)] // Boolean, Array, Object, Print and Mut are reserved, the lexer does not produce them yet
 pub enum TokenKind {
     // Symbols
-    LeftParen, RightParen, LeftBrace, RightBrace,
-    Comma, Dot, Minus, Plus, Semicolon, Slash, Star,
-    QuestionMark, Colon,
+    LeftParen, RightParen, LeftBrace, RightBrace, LeftBracket, RightBracket,
+    Comma, Dot, Minus, Plus, Semicolon, Slash, Star, Percent,
+    QuestionMark, Colon, QuestionQuestion, QuestionDot,
 
     // Operators
     Bang, BangEqual,
     Equal, EqualEqual,
     Greater, GreaterEqual,
     Less, LessEqual,
+    PlusEqual, MinusEqual, StarEqual, SlashEqual, PercentEqual,
+    Arrow,
 
     // Literals
-    Identifier, String, Number, Boolean, Array, Object,
+    Identifier, String, Interpolation, Number, Boolean, Array, Object,
 
     // Keywords
     And, Class, Else, False, True, Fn, For,
@@ -27,9 +30,28 @@ pub enum TokenKind {
     Break, Continue,
 
     // End of file
+    #[allow(clippy::upper_case_acronyms)]
     EOF
 }
 
+/// Byte range `start..end` of the source that a token or node covers.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Span {
+    pub(crate) start: usize,
+    pub(crate) end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    /// Span covering from the start of `self` to the end of `other`.
+    pub fn to(self, other: Span) -> Span {
+        Span::new(self.start.min(other.start), self.end.max(other.end))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Token {
     pub(crate) kind: TokenKind,
@@ -37,6 +59,7 @@ pub struct Token {
     pub(crate) literal: Option<Value>,
     pub(crate) line_number: usize,
     pub(crate) column_number: usize,
+    pub(crate) span: Span,
     pub(crate) parent_context: Option<String>, // for now a String
     pub(crate) typed_token: Option<TypedToken>,
     pub(crate) is_mutable: bool,
@@ -52,6 +75,7 @@ impl PartialEq for Token {
             && self.literal == other.literal
             && self.line_number == other.line_number
             && self.column_number == other.column_number
+            && self.span == other.span
             && self.parent_context == other.parent_context
             && self.typed_token == other.typed_token
             && self.is_mutable == other.is_mutable
@@ -76,6 +100,7 @@ impl Token {
             literal: None,
             line_number,
             column_number,
+            span: Span::new(end_offset.saturating_sub(lexeme.len()), end_offset),
             parent_context: None,
             typed_token: None,
             is_mutable: false,
@@ -94,14 +119,17 @@ impl Token {
         match kind {
             // Symbols
             TokenKind::LeftParen | TokenKind::RightParen | TokenKind::LeftBrace | TokenKind::RightBrace
+            | TokenKind::LeftBracket | TokenKind::RightBracket
             | TokenKind::Comma | TokenKind::Dot | TokenKind::Minus | TokenKind::Plus
-            | TokenKind::Semicolon | TokenKind::Slash | TokenKind::Star | TokenKind::QuestionMark
-            | TokenKind::Colon => Ok(Self {
+            | TokenKind::Semicolon | TokenKind::Slash | TokenKind::Star | TokenKind::Percent
+            | TokenKind::QuestionMark | TokenKind::Colon | TokenKind::QuestionQuestion
+            | TokenKind::QuestionDot => Ok(Self {
                 lexeme: Self::read_lexeme(&kind).to_string(),
                 kind,
                 literal: None,
                 line_number,
                 column_number,
+                span: Span::default(),
                 parent_context: None,
                 typed_token: None,
                 is_mutable: false,
@@ -112,13 +140,16 @@ impl Token {
     
             // Operators
             TokenKind::Bang | TokenKind::BangEqual | TokenKind::Equal | TokenKind::EqualEqual
-            | TokenKind::Greater | TokenKind::GreaterEqual | TokenKind::Less | TokenKind::LessEqual => {
+            | TokenKind::Greater | TokenKind::GreaterEqual | TokenKind::Less | TokenKind::LessEqual
+            | TokenKind::PlusEqual | TokenKind::MinusEqual | TokenKind::StarEqual | TokenKind::SlashEqual
+            | TokenKind::PercentEqual | TokenKind::Arrow => {
                 Ok(Self {
                     lexeme: Self::read_lexeme(&kind).to_string(),
                     kind,
                     literal: None,
                     line_number,
                     column_number,
+                    span: Span::default(),
                     parent_context: None,
                     typed_token: None,
                     is_mutable: false,
@@ -139,6 +170,7 @@ impl Token {
                 literal: None,
                 line_number,
                 column_number,
+                span: Span::default(),
                 parent_context: None,
                 typed_token: None,
                 is_mutable: false,
@@ -154,6 +186,7 @@ impl Token {
                 literal: None,
                 line_number,
                 column_number,
+                span: Span::default(),
                 parent_context: None,
                 typed_token: None,
                 is_mutable: false,
@@ -187,6 +220,7 @@ impl Token {
                     literal: None, // Identifiers usually don't have a literal value.
                     line_number,
                     column_number,
+                    span: Span::default(),
                     parent_context: None,
                     typed_token: None,
                     is_mutable: false,
@@ -195,13 +229,14 @@ impl Token {
                     source_file: Some(source_file),
                 })
             },
-            TokenKind::String => {
+            TokenKind::String | TokenKind::Interpolation => { // an Interpolation is the part of a string before a `${`
                 Ok(Self {
                     lexeme: lexeme.to_string(),
                     kind,
                     literal: Some(Value::String(lexeme.to_string())), // maybe it modifies so thats why its String
                     line_number,
                     column_number,
+                    span: Span::default(),
                     parent_context: None,
                     typed_token: None,
                     is_mutable: false,
@@ -212,7 +247,10 @@ impl Token {
             },
             TokenKind::Number => {
 
-                let num = lexeme.parse::<f64>().unwrap();
+                let num = match lexeme.parse::<f64>() {
+                    Ok(num) => num,
+                    Err(_) => return Err(format!("Invalid number literal '{}' in line {} column {}", lexeme, line_number, column_number))
+                };
 
                 Ok(Self {
                     lexeme: lexeme.to_string(),
@@ -220,6 +258,7 @@ impl Token {
                     literal: Some(Value::Number(num)),
                     line_number,
                     column_number,
+                    span: Span::default(),
                     parent_context: None,
                     typed_token: None,
                     is_mutable: false,
@@ -238,6 +277,7 @@ impl Token {
                     literal: Some(Value::Boolean(bool)),
                     line_number,
                     column_number,
+                    span: Span::default(),
                     parent_context: None,
                     typed_token: None,
                     is_mutable: false,
@@ -253,6 +293,7 @@ impl Token {
                     literal: Some(Value::Array(vec![])),
                     line_number,
                     column_number,
+                    span: Span::default(),
                     parent_context: None,
                     typed_token: None,
                     is_mutable: false,
@@ -268,6 +309,7 @@ impl Token {
                     literal: Some(Value::Object(HashMap::new())),
                     line_number,
                     column_number,
+                    span: Span::default(),
                     parent_context: None,
                     typed_token: None,
                     is_mutable: false,
@@ -276,37 +318,34 @@ impl Token {
                     source_file: Some(source_file),
                 })
             },
-            _ => return Err(format!("An error ocurred in the Tokenization process in {} \nline: {}\ncolumn: {}", lexeme, line_number, column_number))
+            _ => Err(format!("An error ocurred in the Tokenization process in {} \nline: {}\ncolumn: {}", lexeme, line_number, column_number))
         }
     }
 
+    /// Sets the byte range of the source the token was read from.
+    pub fn with_span(mut self, start: usize, end: usize) -> Self {
+        self.span = Span::new(start, end);
+        self
+    }
+
     pub fn eof(
         source_file: String,
         line_number: usize,
         column_number: usize
     ) -> Self {
-        Self {
-            kind: TokenKind::EOF,
-            lexeme: "End of File".to_string(),
-            literal: None,
-            line_number,
-            column_number,
-            parent_context: None,
-            typed_token: None,
-            is_mutable: false,
-            access_specifier: None,
-            annotations: None,
-            source_file: Some(source_file),
-        }
+        // the lexer gives it its span
+        Self::new(TokenKind::EOF, "End of File".to_string(), line_number, column_number, 0, source_file)
     }
 
-    fn read_lexeme(kind: &TokenKind) -> &'static str {
+    pub(crate) fn read_lexeme(kind: &TokenKind) -> &'static str {
         match kind {
             // Symbols
             TokenKind::LeftParen => "(",
             TokenKind::RightParen => ")",
             TokenKind::LeftBrace => "{",
             TokenKind::RightBrace => "}",
+            TokenKind::LeftBracket => "[",
+            TokenKind::RightBracket => "]",
             TokenKind::Comma => ",",
             TokenKind::Dot => ".",
             TokenKind::Minus => "-",
@@ -314,8 +353,11 @@ impl Token {
             TokenKind::Semicolon => ";",
             TokenKind::Slash => "/",
             TokenKind::Star => "*",
+            TokenKind::Percent => "%",
             TokenKind::QuestionMark => "?",
             TokenKind::Colon => ":",
+            TokenKind::QuestionQuestion => "??",
+            TokenKind::QuestionDot => "?.",
 
             // Operators (still symbols I guess)
             TokenKind::Bang => "!",
@@ -326,6 +368,12 @@ impl Token {
             TokenKind::GreaterEqual => ">=",
             TokenKind::Less => "<",
             TokenKind::LessEqual => "<=",
+            TokenKind::PlusEqual => "+=",
+            TokenKind::MinusEqual => "-=",
+            TokenKind::StarEqual => "*=",
+            TokenKind::SlashEqual => "/=",
+            TokenKind::PercentEqual => "%=",
+            TokenKind::Arrow => "=>",
 
             // Keywords
             TokenKind::And => "and",
@@ -333,7 +381,7 @@ impl Token {
             TokenKind::Else => "else",
             TokenKind::False => "false",
             TokenKind::True => "true",
-            TokenKind::Fn => "Fn",
+            TokenKind::Fn => "fn",
             TokenKind::For => "for",
             TokenKind::If => "if",
             TokenKind::Null => "null",
@@ -341,7 +389,7 @@ impl Token {
             TokenKind::Print => "print",
             TokenKind::Return => "return",
             TokenKind::Super => "super",
-            TokenKind::This => "this",
+            TokenKind::This => "self",
             TokenKind::Let => "let",
             TokenKind::While => "while",
             TokenKind::Enum => "enum",
@@ -362,6 +410,7 @@ impl Token {
 
 //TODO
 #[derive(Debug, Clone, PartialEq)]
+#[allow(dead_code)] // for a type checker, nothing builds these yet
 enum DataType {
     Number,
     String,
@@ -369,6 +418,7 @@ enum DataType {
     Array(Box<DataType>),
     Object,
     Fnction(Vec<DataType>, Box<DataType>), // arguments, return values
+    // the checker reads enums straight from their declarations instead
     Enum {
         name: String,                             // Enum name
         variants: HashMap<String, DataType>, // Variant name and associated types
@@ -384,6 +434,7 @@ pub struct TypedToken {
 }
 //TODO
 #[derive(Debug, Clone, PartialEq)]
+#[allow(dead_code)] // nothing parses `pub` or `private` yet
 pub enum AccessSpecifier {
     Public,
     Private
@@ -534,14 +585,14 @@ mod tests {
             ("else", TokenKind::Else),
             ("false", TokenKind::False),
             ("true", TokenKind::True),
-            ("Fn", TokenKind::Fn),
+            ("fn", TokenKind::Fn),
             ("for", TokenKind::For),
             ("if", TokenKind::If),
             ("null", TokenKind::Null),
             ("or", TokenKind::Or),
             ("return", TokenKind::Return),
             ("super", TokenKind::Super),
-            ("this", TokenKind::This),
+            ("self", TokenKind::This),
             ("let", TokenKind::Let),
             ("while", TokenKind::While),
             ("enum", TokenKind::Enum),
@@ -566,6 +617,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_token_new_span() {
+        let token = Token::new(
+            TokenKind::Identifier,
+            "example".to_string(),
+            1,
+            5,
+            12,
+            "test.rs".to_string(),
+        );
+        assert_eq!(token.span, Span::new(5, 12));
+    }
+
+    #[test]
+    fn test_span_to() {
+        assert_eq!(Span::new(2, 4).to(Span::new(7, 9)), Span::new(2, 9));
+        assert_eq!(Span::new(7, 9).to(Span::new(2, 4)), Span::new(2, 9));
+    }
+
     #[test]
     fn test_access_specifier() {
         assert_eq!(AccessSpecifier::Public, AccessSpecifier::Public);
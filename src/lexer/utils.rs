@@ -1,20 +1,19 @@
 use std::{fs::File, io::{self, BufRead}, path::Path};
 
-pub fn read_file(filename: &str) -> String {
+/// The contents of `filename` with every line ended by a newline, or why it could not be read.
+pub fn read_file(filename: &str) -> io::Result<String> {
     let mut result = String::new();
-    if let Ok(lines) = read_lines(filename) {
-        for line in lines.flatten() {
-            result.push_str(&line);
-            result.push('\n');
-        }
+    for line in read_lines(filename)? {
+        result.push_str(&line?);
+        result.push('\n');
     }
-    result
+    Ok(result)
 }
 
 fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
 where P: AsRef<Path>,
 {
-    let file = File::open(filename).expect("File name was not found");
+    let file = File::open(filename)?;
     Ok(io::BufReader::new(file).lines())
 }
 
@@ -46,7 +45,7 @@ impl StringStream {
     /// Consumes the current element and moves the position forward.
     pub fn next(&mut self) -> Option<char> {
         if self.position < self.input.len() {
-            let current = self.input[self.position].clone();
+            let current = self.input[self.position];
             self.position += 1;
             Some(current)
         } else {
@@ -78,7 +77,7 @@ mod tests {
         temp_file.write_all(content.as_bytes()).expect("Failed to write to file");
 
         // Call `read_file` to read the file's contents
-        let result = read_file(temp_file_path);
+        let result = read_file(temp_file_path).expect("Failed to read file");
 
         // The expected output as a String
         let expected = "Hello\nWorld\n".to_string();
@@ -90,6 +89,13 @@ mod tests {
         fs::remove_file(temp_file_path).expect("Failed to remove temporary file");
     }
 
+    #[test]
+    fn test_read_missing_file() {
+        let error = read_file("no_such_file.txt").unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+
     #[test]
     fn test_stream_peek_and_next() {
         let input = String::from("String");
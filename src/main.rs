@@ -1,25 +1,57 @@
-use std::{env, io::{self, BufRead, Write}};
+use std::{env, io::{self, BufRead, IsTerminal, Write}, process, thread};
+
+use checker::Checker;
+use diagnostics::diagnostic::{Diagnostic, Renderer};
+use interpreter::interpreter::{Interpreter, STACK_SIZE};
+use lexer::{lexer::Lexer, utils::read_file};
+use parser::parser::Parser;
 
 mod lexer;
 mod ast;
 mod parser;
 mod stmt;
+mod checker;
+mod diagnostics;
+mod interpreter;
 
 fn main() {
     
     let args: Vec<String> = env::args().collect();
 
-    if args.len() == 3 {
-        println!("args 3");
-    } else if args.len() == 2 {
-        println!("args 2");
-    } else if args.len() == 1 {
-        println!("args 1");
+    // scripts run on a thread with a bigger stack, deep recursion is a runtime error before it runs out
+    let interpreter = thread::Builder::new().stack_size(STACK_SIZE).spawn(move || match args.len() {
+        1 => run_prompt(),
+        2 => run_file(&args[1]),
+        _ => Err("Usage: langscript [script]".to_string()),
+    });
+    let result = interpreter.map_err(|err| err.to_string()).and_then(|handle| {
+        handle.join().unwrap_or_else(|_| Err("The interpreter panicked".to_string()))
+    });
+
+    if let Err(message) = result {
+        eprintln!("{}", message);
+        process::exit(1);
+    }
+}
+
+fn run_file(path: &str) -> Result<(), String> {
+    let source = read_file(path).map_err(|err| format!("Could not read {}: {}", path, err))?;
+    let source_file: &'static str = Box::leak(path.to_string().into_boxed_str()); // the lexer wants it for the whole run
+
+    let mut interpreter = Interpreter::new();
+    let mut checker = Checker::new();
+
+    if run(&mut interpreter, &mut checker, source_file, source, false) {
+        Ok(())
+    } else {
+        Err(format!("Could not run {}", path))
     }
 }
 
-#[allow(dead_code)]
 fn run_prompt() -> Result<(), String> {
+    let mut interpreter = Interpreter::new();
+    let mut checker = Checker::new();
+
     loop {
         print!(">>");
         
@@ -44,7 +76,44 @@ fn run_prompt() -> Result<(), String> {
             Err(_) => return Err("Could not read line".to_string())
         }
 
-        println!("<< {}", buffer);
+        run(&mut interpreter, &mut checker, "<stdin>", buffer, true);
 
     }
-}
\ No newline at end of file
+}
+
+// prints every diagnostic on the way, returns false if anything went wrong. Warnings dont stop
+// the program from running
+fn run(interpreter: &mut Interpreter, checker: &mut Checker, source_file: &'static str, source: String, echo: bool) -> bool {
+    let renderer = Renderer::new(source_file, &source).with_colour(io::stderr().is_terminal());
+    let report = |diagnostic: Diagnostic| eprint!("{}", renderer.render(&diagnostic));
+
+    let tokens = match Lexer::new(source_file, source.clone()).tokenize() {
+        Ok(tokens) => tokens,
+        Err(errors) => {
+            errors.iter().for_each(|error| report(Diagnostic::from(error)));
+            return false;
+        }
+    };
+
+    let statements = match Parser::new(tokens).parse() {
+        Ok(statements) => statements,
+        Err(errors) => {
+            errors.iter().for_each(|error| report(Diagnostic::from(error)));
+            return false;
+        }
+    };
+
+    checker.check(&statements).iter().for_each(|warning| report(Diagnostic::from(warning)));
+
+    match interpreter.interpret(&statements) {
+        Ok(Some(value)) if echo => {
+            println!("<< {}", value);
+            true
+        },
+        Ok(_) => true,
+        Err(error) => {
+            report(Diagnostic::from(&error));
+            false
+        }
+    }
+}
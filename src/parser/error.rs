@@ -0,0 +1,106 @@
+use crate::{
+    diagnostics::error::{Error, ErrorKind},
+    lexer::tokens::{Token, TokenKind},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
+    ExpectedToken {
+        expected: TokenKind,
+        found: String,   // the token that was there instead, already described
+        context: String, // where it was expected, like "after arguments"
+    },
+    ExpectedExpression(String),
+    UnclosedInterpolation,
+    InvalidAssignmentTarget(String), // the operator, `=` or a compound one
+    OutsideLoop(String),             // `break` or `continue`
+    UnknownLabel(String),
+    LabelWithoutLoop(String),
+    ReturnOutsideFunction,
+    ExpectedPropertyName(String),
+    SelfOutsideClass,
+    SuperOutsideSubclass,
+    ExpectedPattern(String),
+    TooManyArguments,
+    TooManyParameters(String),
+}
+
+pub type ParseError = Error<ParseErrorKind>;
+
+impl ErrorKind for ParseErrorKind {
+    fn code(&self) -> &'static str {
+        match self {
+            ParseErrorKind::ExpectedToken { .. } => "P0001",
+            ParseErrorKind::ExpectedExpression(_) => "P0002",
+            ParseErrorKind::UnclosedInterpolation => "P0003",
+            ParseErrorKind::InvalidAssignmentTarget(_) => "P0004",
+            ParseErrorKind::OutsideLoop(_) => "P0005",
+            ParseErrorKind::UnknownLabel(_) => "P0006",
+            ParseErrorKind::LabelWithoutLoop(_) => "P0007",
+            ParseErrorKind::ReturnOutsideFunction => "P0008",
+            ParseErrorKind::ExpectedPropertyName(_) => "P0009",
+            ParseErrorKind::SelfOutsideClass => "P0010",
+            ParseErrorKind::SuperOutsideSubclass => "P0011",
+            ParseErrorKind::ExpectedPattern(_) => "P0012",
+            ParseErrorKind::TooManyArguments => "P0100",
+            ParseErrorKind::TooManyParameters(_) => "P0101",
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            ParseErrorKind::ExpectedToken { expected, found, context } => {
+                format!("Expected {} {}, found {}", describe_kind(expected), context, found)
+            },
+            ParseErrorKind::ExpectedExpression(found) => format!("Expected expression, found {}", found),
+            ParseErrorKind::UnclosedInterpolation => "Expected '}' to close interpolation".to_string(),
+            ParseErrorKind::InvalidAssignmentTarget(operator) => format!("Invalid left side of '{}'", operator),
+            ParseErrorKind::OutsideLoop(keyword) => format!("'{}' outside of a loop", keyword),
+            ParseErrorKind::UnknownLabel(label) => format!("No enclosing loop is labelled '{}'", label),
+            ParseErrorKind::LabelWithoutLoop(label) => format!("Label '{}' must be followed by a loop", label),
+            ParseErrorKind::ReturnOutsideFunction => "'return' outside of a function".to_string(),
+            ParseErrorKind::ExpectedPropertyName(found) => format!("Expected property name, found {}", found),
+            ParseErrorKind::SelfOutsideClass => "'self' outside of a class".to_string(),
+            ParseErrorKind::SuperOutsideSubclass => "'super' outside of a subclass".to_string(),
+            ParseErrorKind::ExpectedPattern(found) => format!("Expected pattern, found {}", found),
+            ParseErrorKind::TooManyArguments => "Function cant have more than 255 arguments".to_string(),
+            ParseErrorKind::TooManyParameters(name) => format!("More than 255 parameters in function '{}'", name),
+        }
+    }
+}
+
+/// How a token is shown in error messages.
+pub fn describe(token: &Token) -> String {
+    match token.kind {
+        TokenKind::EOF => "end of file".to_string(),
+        _ => format!("'{}'", token.lexeme),
+    }
+}
+
+fn describe_kind(kind: &TokenKind) -> String {
+    match kind {
+        TokenKind::Identifier => "identifier".to_string(),
+        TokenKind::String => "string".to_string(),
+        TokenKind::Number => "number".to_string(),
+        TokenKind::EOF => "end of file".to_string(),
+        _ => format!("'{}'", Token::read_lexeme(kind)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expected_token_message() {
+        let found = Token::static_tokenkind("filename".to_string(), TokenKind::Semicolon, 2, 4).unwrap();
+        let error = ParseError::at(ParseErrorKind::ExpectedToken {
+            expected: TokenKind::RightParen,
+            found: describe(&found),
+            context: "after arguments".to_string(),
+        }, &found);
+
+        assert_eq!(error.to_string(), "Expected ')' after arguments, found ';' in line 2 column 4");
+        assert_eq!(error.code(), "P0001");
+    }
+}
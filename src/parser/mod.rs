@@ -1,2 +1,4 @@
+#[allow(clippy::module_inception)]
 pub mod parser;
-pub mod utils;
\ No newline at end of file
+pub mod utils;
+pub mod error;
\ No newline at end of file
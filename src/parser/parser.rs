@@ -1,144 +1,497 @@
-use std::vec;
+use std::{rc::Rc, vec};
 
-use crate::{ast::{expr::Expr, value::Value}, lexer::tokens::{Token, TokenKind}, stmt::stmt::Stmt};
+use crate::{ast::{expr::{Expr, Key}, pattern::{Arm, Pattern}, value::Value}, lexer::tokens::{Span, Token, TokenKind}, stmt::stmt::Stmt};
 
-use super::utils::{match_token, match_tokens, TokenStream};
+use super::{error::{describe, ParseError, ParseErrorKind}, utils::{match_token, match_tokens, TokenStream}};
 
 pub struct Parser {
     pub(crate) tokens: TokenStream,
-    current: usize,
-    length: usize
+    loops: Vec<Option<String>>, // labels of the loops around the statement being parsed, innermost last
+    function_depth: usize,      // how many function bodies the parser is inside of, for `return`
+    classes: Vec<bool>,         // for each class body around, whether it has a superclass, for `self` and `super`
+    reported: Vec<ParseError>,  // errors that did not stop the parser, `parse` collects them
 }
 
-// for future develop: in functions or a method in a class
+// a plain function or a method in a class, named in error messages
 #[derive(Debug)]
 enum FunctionKind {
     Function,
     Method
 }
 
+impl FunctionKind {
+    fn name(&self) -> &'static str {
+        match self {
+            FunctionKind::Function => "function",
+            FunctionKind::Method => "method",
+        }
+    }
+}
+
 impl Parser {
 
     pub fn new(tokens: Vec<Token>) -> Self {
         Self {
-            length: tokens.len(), // isnt in order because of borrow checker
             tokens: TokenStream::new(tokens),
-            current: 0,
+            loops: vec![],
+            function_depth: 0,
+            classes: vec![],
+            reported: vec![],
         }
     }
 
-    pub fn parse(&mut self) -> Result<Vec<Stmt>, String> {
+    pub fn parse(&mut self) -> Result<Vec<Stmt>, Vec<ParseError>> {
         
         let mut stmts = vec![];
         let mut errors = vec![];
 
         while !self.tokens.is_eof() {
             let stmt = self.declaration();
+            errors.append(&mut self.reported);
             match stmt {
                 Ok(s) => stmts.push(s),
-                Err(msg) => {
-                    errors.push(msg);
+                Err(err) => {
+                    errors.push(err);
                     self.synchronize();
                 },
             }
         }
         
-        if errors.len() == 0 {
+        if errors.is_empty() {
             Ok(stmts)
         } else {
-            Err(errors.join("\n"))
+            Err(errors)
         }
 
     }
 
-    fn declaration(&mut self) -> Result<Stmt, String> {
-        if match_token(self, &TokenKind::Fn) {
+    fn declaration(&mut self) -> Result<Stmt, ParseError> {
+        // only a named `fn` is a declaration, `fn (a) { ... }` is an anonymous function expression
+        if self.check(TokenKind::Fn) && self.tokens.peek_next().is_some_and(|token| token.kind == TokenKind::Identifier) {
+            self.tokens.next();
             self.function_declaration(&FunctionKind::Function)
+        } else if match_token(self, &TokenKind::Class) {
+            self.class_declaration()
+        } else if match_token(self, &TokenKind::Enum) {
+            self.enum_declaration()
+        } else if match_token(self, &TokenKind::Let) {
+            self.let_declaration()
         } else {
             self.statement()
         }
     }
 
-    fn primary(&mut self) -> Result<Expr, String> {
-        let token = self.tokens.peek();
-        let result;
-
-        match &token.clone().unwrap().kind {
+    fn primary(&mut self) -> Result<Expr, ParseError> {
+        let token = self.tokens.peek().unwrap().clone();
+        let result = match &token.kind {
+            TokenKind::LeftParen if self.is_arrow_function() => {
+                self.tokens.next();
+                self.arrow_function()?
+            },
             TokenKind::LeftParen => {
                 self.tokens.next();
                 let expr = self.expression()?;
-                self.tokens.consume(TokenKind::RightParen,
-                    format!(
-                        "Expected ')' in line {} column {}",
-                        self.tokens.peek().unwrap().line_number,
-                        self.tokens.peek().unwrap().column_number
-                    ).as_str()
-                );
-                result = Expr::Grouping { expression: Box::new(expr) }
-            },
-            TokenKind::False | TokenKind::True | TokenKind::Number | TokenKind::String => {
+                let paren = self.tokens.consume(TokenKind::RightParen, "after expression")
+                    .map_err(|err| err.with_label(token.span, "opening parenthesis here"))?;
+                Expr::Grouping { expression: Box::new(expr), span: token.span.to(paren.span) }
+            },
+            TokenKind::False | TokenKind::True | TokenKind::Null | TokenKind::Number | TokenKind::String => {
                 self.tokens.next(); // why the fuck
-                result = Expr::Literal { value: Value::from_token(token.unwrap().clone()) }
+                Expr::Literal { value: Value::from_token(token.clone()), span: token.span }
+            },
+            TokenKind::Interpolation => {
+                self.tokens.next();
+                self.interpolation(token)?
             },
             TokenKind::Identifier => {
-                todo!() // for variables
+                self.tokens.next();
+                Expr::Variable { span: token.span, name: token }
+            },
+            TokenKind::This => {
+                // the expression is still fine to parse, so the rest of the statement is not reported too
+                if self.classes.is_empty() {
+                    self.reported.push(ParseError::at(ParseErrorKind::SelfOutsideClass, &token)
+                        .with_help("`self` can only be used inside the methods of a class"));
+                }
+                self.tokens.next();
+                Expr::This { span: token.span, keyword: token }
+            },
+            TokenKind::Super => {
+                if self.classes.last() != Some(&true) {
+                    self.reported.push(ParseError::at(ParseErrorKind::SuperOutsideSubclass, &token)
+                        .with_help("`super` can only be used inside the methods of a class with a superclass, like `class B < A`"));
+                }
+                self.tokens.next();
+                self.tokens.consume(TokenKind::Dot, "after 'super'")?;
+                let method = self.tokens.consume(TokenKind::Identifier, "as method name after 'super.'")?;
+                Expr::Super { span: token.span.to(method.span), keyword: token, method: Box::new(method) }
             },
             TokenKind::Fn => {
-                todo!() //anonymous functions
+                self.tokens.next();
+                self.function_expression()?
             },
-            ttype => return Err(format!("Expected expression, last token read was {:?} in line {} column {}",
-                ttype, 
-                token.unwrap().line_number,
-                token.unwrap().column_number
-            ))
-        }
+            TokenKind::Match => {
+                self.tokens.next();
+                self.match_expression()?
+            },
+            TokenKind::LeftBracket => {
+                self.tokens.next();
+                self.array()?
+            },
+            TokenKind::LeftBrace => {
+                self.tokens.next();
+                self.object()?
+            },
+            _ => return Err(ParseError::at(ParseErrorKind::ExpectedExpression(describe(&token)), &token))
+        };
         Ok(result)
     }
 
-    fn statement(&mut self) -> Result<Stmt, String> {
-        if match_token(self, &TokenKind::LeftBrace) {
+    // a string like "a ${b} c" arrives as Interpolation("a "), the tokens of `b`, String(" c")
+    fn interpolation(&mut self, first: Token) -> Result<Expr, ParseError> {
+        let span = first.span;
+        let mut parts = vec![Expr::Literal { value: Value::from_token(first), span }];
+
+        loop {
+            parts.push(self.expression()?);
+
+            if match_token(self, &TokenKind::Interpolation) {
+                let part = self.tokens.previous().unwrap();
+                parts.push(Expr::Literal { span: part.span, value: Value::from_token(part) });
+            } else if match_token(self, &TokenKind::String) {
+                let end = self.tokens.previous().unwrap();
+                let span = span.to(end.span);
+                parts.push(Expr::Literal { span: end.span, value: Value::from_token(end) });
+                return Ok(Expr::Interpolation { parts, span });
+            } else {
+                return Err(ParseError::at(ParseErrorKind::UnclosedInterpolation, self.tokens.peek().unwrap())
+                    .with_label(span, "string starts here"));
+            }
+        }
+    }
+
+    // the `let` is already consumed
+    fn let_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let keyword = self.tokens.previous().unwrap();
+        let name = self.tokens.consume(TokenKind::Identifier, "as variable name")?;
+
+        let initializer = if match_token(self, &TokenKind::Equal) {
+            Some(self.expression()?)
+        } else {
+            None
+        };
+
+        let semicolon = self.tokens.consume(TokenKind::Semicolon, "after variable declaration")?;
+
+        Ok(Stmt::Let { name, initializer, span: keyword.span.to(semicolon.span) })
+    }
+
+    fn statement(&mut self) -> Result<Stmt, ParseError> {
+        if self.check(TokenKind::LeftBrace) && !self.is_object_literal() {
+            self.tokens.next();
             self.block_statement()
+        } else if match_token(self, &TokenKind::If) {
+            self.if_statement()
+        } else if match_token(self, &TokenKind::While) {
+            self.while_statement(None)
+        } else if match_token(self, &TokenKind::For) {
+            self.for_statement(None)
+        } else if match_tokens(self, &[TokenKind::Break, TokenKind::Continue]) {
+            self.jump_statement()
+        } else if match_token(self, &TokenKind::Return) {
+            self.return_statement()
+        } else if self.check(TokenKind::Identifier)
+            && self.tokens.peek_next().is_some_and(|token| token.kind == TokenKind::Colon) {
+            self.labelled_statement()
         } else {
             self.expression_stmt()
         }
     }
 
-    fn expression_stmt(&mut self) -> Result<Stmt,String> {
+    // the `if` is already consumed, `else if` is just an `else` whose branch is another if
+    fn if_statement(&mut self) -> Result<Stmt, ParseError> {
+        let keyword = self.tokens.previous().unwrap();
+
+        self.tokens.consume(TokenKind::LeftParen, "after 'if'")?;
+        let condition = self.expression()?;
+        self.tokens.consume(TokenKind::RightParen, "after if condition")?;
+
+        let then_branch = self.statement()?;
+        let else_branch = if match_token(self, &TokenKind::Else) {
+            Some(Box::new(self.statement()?))
+        } else {
+            None
+        };
+
+        let end = else_branch.as_ref().map_or(then_branch.span(), |branch| branch.span());
+
+        Ok(Stmt::If {
+            condition,
+            then_branch: Box::new(then_branch),
+            else_branch,
+            span: keyword.span.to(end)
+        })
+    }
+
+    // `label: while ...`, nothing is consumed yet
+    fn labelled_statement(&mut self) -> Result<Stmt, ParseError> {
+        let label = self.tokens.next().unwrap();
+        self.tokens.next(); // the `:`
+
+        if match_token(self, &TokenKind::While) {
+            self.while_statement(Some(label))
+        } else if match_token(self, &TokenKind::For) {
+            self.for_statement(Some(label))
+        } else {
+            Err(ParseError::at(ParseErrorKind::LabelWithoutLoop(label.lexeme.clone()), &label)
+                .with_help("only `while` and `for` loops can have labels"))
+        }
+    }
+
+    // the `break` or `continue` is already consumed
+    fn jump_statement(&mut self) -> Result<Stmt, ParseError> {
+        let keyword = self.tokens.previous().unwrap();
+
+        if self.loops.is_empty() {
+            return Err(ParseError::at(ParseErrorKind::OutsideLoop(keyword.lexeme.clone()), &keyword));
+        }
+
+        let label = if self.check(TokenKind::Identifier) {
+            let label = self.tokens.next().unwrap();
+            if !self.loops.iter().any(|name| name.as_ref() == Some(&label.lexeme)) {
+                return Err(ParseError::at(ParseErrorKind::UnknownLabel(label.lexeme.clone()), &label));
+            }
+            Some(label)
+        } else {
+            None
+        };
+
+        let semicolon = self.tokens.consume(TokenKind::Semicolon, &format!("after '{}'", keyword.lexeme))?;
+        let span = keyword.span.to(semicolon.span);
+
+        if keyword.kind == TokenKind::Break {
+            Ok(Stmt::Break { label, span })
+        } else {
+            Ok(Stmt::Continue { label, span })
+        }
+    }
+
+    // the `return` is already consumed
+    fn return_statement(&mut self) -> Result<Stmt, ParseError> {
+        let keyword = self.tokens.previous().unwrap();
+
+        if self.function_depth == 0 {
+            return Err(ParseError::at(ParseErrorKind::ReturnOutsideFunction, &keyword));
+        }
+
+        let value = if self.check(TokenKind::Semicolon) {
+            None
+        } else {
+            Some(self.expression()?)
+        };
+
+        let semicolon = self.tokens.consume(TokenKind::Semicolon, "after return value")?;
+
+        Ok(Stmt::Return { value, span: keyword.span.to(semicolon.span) })
+    }
+
+    // parses a loop body, with `label` in scope for the `break`s and `continue`s inside it
+    fn loop_body(&mut self, label: &Option<Token>) -> Result<Stmt, ParseError> {
+        self.loops.push(label.as_ref().map(|label| label.lexeme.clone()));
+        let body = self.statement();
+        self.loops.pop();
+        body
+    }
+
+    // the `while` is already consumed
+    fn while_statement(&mut self, label: Option<Token>) -> Result<Stmt, ParseError> {
+        let keyword = self.tokens.previous().unwrap();
+        let start = label.as_ref().map_or(keyword.span, |label| label.span);
+
+        self.tokens.consume(TokenKind::LeftParen, "after 'while'")?;
+        let condition = self.expression()?;
+        self.tokens.consume(TokenKind::RightParen, "after while condition")?;
+
+        let body = self.loop_body(&label)?;
+
+        Ok(Stmt::While { label, condition, span: start.to(body.span()), body: Box::new(body) })
+    }
+
+    // the `for` is already consumed, every clause of `for (init; cond; step)` can be left empty
+    fn for_statement(&mut self, label: Option<Token>) -> Result<Stmt, ParseError> {
+        let keyword = self.tokens.previous().unwrap();
+        let start = label.as_ref().map_or(keyword.span, |label| label.span);
+
+        self.tokens.consume(TokenKind::LeftParen, "after 'for'")?;
+
+        let initializer = if match_token(self, &TokenKind::Semicolon) {
+            None
+        } else if match_token(self, &TokenKind::Let) {
+            Some(Box::new(self.let_declaration()?))
+        } else {
+            Some(Box::new(self.expression_stmt()?))
+        };
+
+        let condition = if self.check(TokenKind::Semicolon) {
+            None
+        } else {
+            Some(Box::new(self.expression()?))
+        };
+        self.tokens.consume(TokenKind::Semicolon, "after loop condition")?;
+
+        let increment = if self.check(TokenKind::RightParen) {
+            None
+        } else {
+            Some(Box::new(self.expression()?))
+        };
+        self.tokens.consume(TokenKind::RightParen, "after for clauses")?;
+
+        let body = self.loop_body(&label)?;
+
+        Ok(Stmt::For {
+            label,
+            initializer,
+            condition,
+            increment,
+            span: start.to(body.span()),
+            body: Box::new(body)
+        })
+    }
+
+    fn expression_stmt(&mut self) -> Result<Stmt, ParseError> {
         let expression = self.expression()?;
 
-        self.tokens.consume(TokenKind::Semicolon, 
-            format!("Expected ';' after block statement in line {} column {}",
-            self.tokens.peek().unwrap().line_number,
-            self.tokens.peek().unwrap().column_number
-        ).as_str()
-        )?;
+        let semicolon = self.tokens.consume(TokenKind::Semicolon, "after expression")?;
+
+        Ok(Stmt::Expression { span: expression.span().to(semicolon.span), expression })
+    }
+
+    fn expression(&mut self) -> Result<Expr, ParseError> {
+        self.assignment()
+    }
+
+    // right associative, so `a = b = 1` assigns `b` first
+    fn assignment(&mut self) -> Result<Expr, ParseError> {
+        let target = self.conditional()?;
+
+        if match_tokens(self, &[
+            TokenKind::Equal, TokenKind::PlusEqual, TokenKind::MinusEqual,
+            TokenKind::StarEqual, TokenKind::SlashEqual, TokenKind::PercentEqual
+        ]) {
+            let operator = self.tokens.previous().unwrap();
+            let value = self.assignment()?;
+
+            return match target {
+                Expr::Variable { .. } | Expr::Index { .. } | Expr::Get { optional: false, .. } => Ok(Expr::Assign {
+                    span: target.span().to(value.span()),
+                    target: Box::new(target),
+                    operator,
+                    value: Box::new(value),
+                }),
+                _ => Err(ParseError::new(
+                    ParseErrorKind::InvalidAssignmentTarget(operator.lexeme.clone()),
+                    target.span(),
+                    operator.line_number,
+                    operator.column_number
+                ).with_help("only variables, elements and properties can be assigned to")),
+            };
+        }
 
-        Ok(Stmt::Expression { expression })
+        Ok(target)
     }
 
-    fn expression(&mut self) -> Result<Expr,String> {
-        self.unary()
+    // `cond ? a : b`, binds looser than every binary operator and nests to the right, so
+    // `a ? b : c ? d : e` is `a ? b : (c ? d : e)`
+    fn conditional(&mut self) -> Result<Expr, ParseError> {
+        let condition = self.binary(Self::LOWEST_PRECEDENCE)?;
+
+        if !match_token(self, &TokenKind::QuestionMark) {
+            return Ok(condition);
+        }
+
+        let question = self.tokens.previous().unwrap();
+        let then_branch = self.expression()?;
+        self.tokens.consume(TokenKind::Colon, "after the first branch of '?'")
+            .map_err(|err| err.with_label(question.span, "conditional starts here"))?;
+        let else_branch = self.conditional()?;
+
+        Ok(Expr::Conditional {
+            span: condition.span().to(else_branch.span()),
+            condition: Box::new(condition),
+            then_branch: Box::new(then_branch),
+            else_branch: Box::new(else_branch),
+        })
     }
 
-    fn unary(&mut self) -> Result<Expr, String> {
+    const LOWEST_PRECEDENCE: u8 = 1;
+
+    // how tight each binary operator binds, higher binds tighter
+    fn binary_precedence(kind: &TokenKind) -> Option<u8> {
+        match kind {
+            TokenKind::QuestionQuestion => Some(1),
+            TokenKind::Or => Some(2),
+            TokenKind::And => Some(3),
+            TokenKind::EqualEqual | TokenKind::BangEqual => Some(4),
+            TokenKind::Greater | TokenKind::GreaterEqual | TokenKind::Less | TokenKind::LessEqual => Some(5),
+            TokenKind::Plus | TokenKind::Minus => Some(6),
+            TokenKind::Star | TokenKind::Slash | TokenKind::Percent => Some(7),
+            _ => None
+        }
+    }
+
+    // precedence climbing: keeps folding operators that bind at least as tight as `min_precedence`,
+    // parsing each right side one level tighter so operators of the same level associate to the left
+    fn binary(&mut self, min_precedence: u8) -> Result<Expr, ParseError> {
+        let mut left = self.unary()?;
+
+        while let Some(precedence) = Self::binary_precedence(&self.tokens.peek().unwrap().kind) {
+            if precedence < min_precedence {
+                break;
+            }
+
+            let operator = self.tokens.next().unwrap();
+            let right = self.binary(precedence + 1)?;
+
+            left = Expr::Binary {
+                span: left.span().to(right.span()),
+                left: Box::new(left),
+                operator,
+                right: Box::new(right),
+            };
+        }
+
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, ParseError> {
         
         if match_tokens(self, &[TokenKind::Bang, TokenKind::Minus]) {
             let operator = self.tokens.previous().unwrap();
             let right = self.unary()?;
 
-            Ok(Expr::Unary { operator, right: Box::new(right) })
+            Ok(Expr::Unary { span: operator.span.to(right.span()), operator, right: Box::new(right) })
         } else {
             self.call()
         }
 
     }
 
-    fn call(&mut self) -> Result<Expr, String> {
+    fn call(&mut self) -> Result<Expr, ParseError> {
         let mut expr = self.primary()?;
 
         loop {
             if match_token(self, &TokenKind::LeftParen) {
-                expr = self.finish_call(expr)?;
+                expr = self.finish_call(expr, false)?;
+            } else if match_token(self, &TokenKind::LeftBracket) {
+                expr = self.index(expr)?;
+            } else if match_token(self, &TokenKind::Dot) {
+                expr = self.property(expr, false)?;
+            } else if match_token(self, &TokenKind::QuestionDot) {
+                if match_token(self, &TokenKind::LeftParen) {
+                    expr = self.finish_call(expr, true)?;
+                } else {
+                    expr = self.property(expr, true)?;
+                }
             } else {
                 break
             }
@@ -146,7 +499,148 @@ impl Parser {
         Ok(expr)
     }
 
-    fn finish_call(&mut self, callee: Expr) -> Result<Expr, String> {
+    // `object[index]` or a slice `object[start:end]` where either bound can be left out,
+    // the `[` is already consumed
+    fn index(&mut self, object: Expr) -> Result<Expr, ParseError> {
+        let bracket = self.tokens.previous().unwrap();
+
+        let start = if self.check(TokenKind::Colon) {
+            None
+        } else {
+            Some(Box::new(self.expression()?))
+        };
+
+        if match_token(self, &TokenKind::Colon) {
+            let end = if self.check(TokenKind::RightBracket) {
+                None
+            } else {
+                Some(Box::new(self.expression()?))
+            };
+            let close = self.closing_bracket(&bracket, "after slice")?;
+
+            return Ok(Expr::Slice { span: object.span().to(close.span), object: Box::new(object), bracket, start, end });
+        }
+
+        let close = self.closing_bracket(&bracket, "after index")?;
+
+        Ok(Expr::Index {
+            span: object.span().to(close.span),
+            object: Box::new(object),
+            bracket,
+            index: start.unwrap(),
+        })
+    }
+
+    fn closing_bracket(&mut self, open: &Token, context: &str) -> Result<Token, ParseError> {
+        self.tokens.consume(TokenKind::RightBracket, context)
+            .map_err(|err| err.with_label(open.span, "opening bracket here"))
+    }
+
+    // `[1, 2, 3]`, a trailing comma is allowed, the `[` is already consumed
+    fn array(&mut self) -> Result<Expr, ParseError> {
+        let bracket = self.tokens.previous().unwrap();
+        let mut elements = vec![];
+
+        while !self.check(TokenKind::RightBracket) {
+            elements.push(self.expression()?);
+
+            if !match_token(self, &TokenKind::Comma) {
+                break;
+            }
+        }
+
+        let close = self.closing_bracket(&bracket, "after array elements")?;
+        Ok(Expr::Array { elements, span: bracket.span.to(close.span) })
+    }
+
+    // `{ name: 1, "quoted key": 2, [expr]: 3, shorthand }`, a trailing comma is allowed,
+    // the `{` is already consumed
+    fn object(&mut self) -> Result<Expr, ParseError> {
+        let brace = self.tokens.previous().unwrap();
+        let mut properties = vec![];
+
+        while !self.check(TokenKind::RightBrace) {
+            let token = self.tokens.peek().unwrap().clone();
+
+            let property = match token.kind {
+                TokenKind::LeftBracket => {
+                    let bracket = self.tokens.next().unwrap();
+                    let key = self.expression()?;
+                    self.closing_bracket(&bracket, "after computed key")?;
+                    self.tokens.consume(TokenKind::Colon, "after computed key")?;
+                    (Key::Computed { bracket: Box::new(bracket), key: Box::new(key) }, self.expression()?)
+                },
+                TokenKind::Identifier => {
+                    self.tokens.next();
+                    if match_token(self, &TokenKind::Colon) {
+                        (Key::Name(token.lexeme), self.expression()?)
+                    } else {
+                        // `{ name }` is short for `{ name: name }`
+                        (Key::Name(token.lexeme.clone()), Expr::Variable { span: token.span, name: token })
+                    }
+                },
+                TokenKind::String => {
+                    self.tokens.next();
+                    self.tokens.consume(TokenKind::Colon, "after property name")?;
+                    (Key::Name(Value::from_token(token).to_string()), self.expression()?)
+                },
+                _ => return Err(ParseError::at(ParseErrorKind::ExpectedPropertyName(describe(&token)), &token)),
+            };
+            properties.push(property);
+
+            if !match_token(self, &TokenKind::Comma) {
+                break;
+            }
+        }
+
+        let close = self.tokens.consume(TokenKind::RightBrace, "after object properties")
+            .map_err(|err| err.with_label(brace.span, "opening brace here"))?;
+        Ok(Expr::Object { properties, span: brace.span.to(close.span) })
+    }
+
+    // whether the `{` at the current token starts an object literal instead of a block, which
+    // is what it is in statement position unless it looks like `{ key: ...`, `{ a, ...`, `{ a }`
+    // or `{ [expr]: ...`
+    fn is_object_literal(&self) -> bool {
+        let kind = |offset: usize| self.tokens.peek_at(offset).map(|token| token.kind.clone());
+
+        match (kind(1), kind(2)) {
+            // `{ outer: while ... }` is a block starting with a labelled loop
+            (Some(TokenKind::Identifier), Some(TokenKind::Colon)) => !matches!(kind(3), Some(TokenKind::While) | Some(TokenKind::For)),
+            (Some(TokenKind::Identifier), Some(TokenKind::Comma) | Some(TokenKind::RightBrace)) => true,
+            (Some(TokenKind::String), Some(TokenKind::Colon)) => true,
+            (Some(TokenKind::LeftBracket), _) => {
+                // find the matching `]` and look for the `:` after it
+                let mut depth = 0;
+                let mut offset = 1;
+                loop {
+                    match kind(offset) {
+                        Some(TokenKind::LeftBracket) => depth += 1,
+                        Some(TokenKind::RightBracket) => {
+                            depth -= 1;
+                            if depth == 0 {
+                                return kind(offset + 1) == Some(TokenKind::Colon);
+                            }
+                        },
+                        None | Some(TokenKind::EOF) => return false,
+                        _ => (),
+                    }
+                    offset += 1;
+                }
+            },
+            _ => false,
+        }
+    }
+
+    // `object.name`, the `.` or `?.` is already consumed
+    fn property(&mut self, object: Expr, optional: bool) -> Result<Expr, ParseError> {
+        let dot = self.tokens.previous().unwrap();
+        let name = self.tokens.consume(TokenKind::Identifier, &format!("as property name after '{}'", dot.lexeme))?;
+
+        Ok(Expr::Get { span: object.span().to(name.span), object: Box::new(object), name, optional })
+    }
+
+    fn finish_call(&mut self, callee: Expr, optional: bool) -> Result<Expr, ParseError> {
         let mut arguments = vec![];
 
         if !self.check(TokenKind::RightParen) {
@@ -155,8 +649,7 @@ impl Parser {
                 arguments.push(arg);
 
                 if arguments.len() >= 255 {
-                    let location = self.tokens.peek().unwrap().line_number;
-                    return Err(format!("Function cant have more than 255 arguments, in line {}", location))
+                    return Err(ParseError::at(ParseErrorKind::TooManyArguments, self.tokens.peek().unwrap()))
                 }
 
                 if !match_token(self, &TokenKind::Comma) {
@@ -165,46 +658,279 @@ impl Parser {
             }
         }
 
-        let paren = self.tokens.consume(TokenKind::RightParen,
-            format!("Expected ')' after arguments in line {} column {}",
-            self.tokens.peek().unwrap().line_number,
-            self.tokens.peek().unwrap().column_number
-        ).as_str())?;
+        let paren = self.tokens.consume(TokenKind::RightParen, "after arguments")
+            .map_err(|err| err.with_label(callee.span(), "in this call"))?;
 
         Ok(Expr::Call {
+            span: callee.span().to(paren.span),
             callee: Box::new(callee),
             paren,
             arguments,
+            optional,
         })
     }
 
-    fn function_declaration(&mut self, fn_kind: &FunctionKind) -> Result<Stmt, String> {
+    fn function_declaration(&mut self, fn_kind: &FunctionKind) -> Result<Stmt, ParseError> {
+        let keyword = self.tokens.previous().unwrap();
+
+        let name = self.tokens.consume(TokenKind::Identifier, &format!("as {} name", fn_kind.name()))?;
+
+        self.tokens.consume(TokenKind::LeftParen, &format!("after {} name", fn_kind.name()))?;
+
+        let params = self.parameters(&name.lexeme)?;
 
-        let name = self.tokens.consume(TokenKind::Identifier,
-        format!("Expected {fn_kind:?} name in line {} column {}", 
-                self.tokens.peek().unwrap().line_number,
-                self.tokens.peek().unwrap().column_number
-            ).as_str()
-        )?;
+        self.tokens.consume(TokenKind::LeftBrace, &format!("before {} body", fn_kind.name()))?;
+        let (body, body_span) = self.function_body(|parser| parser.block_statement())?;
+
+        Ok(Stmt::Function { 
+            name,
+            params,
+            body,
+            span: keyword.span.to(body_span)
+        })
+    }
+
+    // `class Name { fn method() { ... } ... }` or `class Name < Superclass { ... }`, `:` works too
+    // in place of `<`. The `class` is already consumed
+    fn class_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let keyword = self.tokens.previous().unwrap();
+
+        let name = self.tokens.consume(TokenKind::Identifier, "as class name")?;
+
+        let superclass = if match_tokens(self, &[TokenKind::Less, TokenKind::Colon]) {
+            Some(self.tokens.consume(TokenKind::Identifier, "as superclass name")?)
+        } else {
+            None
+        };
 
-        self.tokens.consume(TokenKind::LeftParen,
-            format!("Expected '(' after {fn_kind:?} named {:?} in line {} column {}",
-                name.lexeme,
-                self.tokens.peek().unwrap().line_number,
-                self.tokens.peek().unwrap().column_number
-            ).as_str()
-        )?;
+        let brace = self.tokens.consume(TokenKind::LeftBrace, "before class body")?;
 
+        self.classes.push(superclass.is_some());
+        let methods = self.class_body();
+        self.classes.pop();
+        let methods = methods?;
+
+        let end = self.tokens.consume(TokenKind::RightBrace, "after class body")
+            .map_err(|err| err.with_label(brace.span, "opening brace here"))?;
+
+        Ok(Stmt::Class { name, superclass, methods, span: keyword.span.to(end.span) })
+    }
+
+    // methods up to the `}` of the class body
+    fn class_body(&mut self) -> Result<Vec<Stmt>, ParseError> {
+        let mut methods = vec![];
+
+        while !self.check(TokenKind::RightBrace) && !self.tokens.is_eof() {
+            self.tokens.consume(TokenKind::Fn, "before method")?;
+            methods.push(self.function_declaration(&FunctionKind::Method)?);
+        }
+
+        Ok(methods)
+    }
+
+    // `match value { pattern => expr, pattern if guard => expr, ... }`, a trailing comma is allowed,
+    // the `match` is already consumed
+    fn match_expression(&mut self) -> Result<Expr, ParseError> {
+        let keyword = self.tokens.previous().unwrap();
+
+        let value = self.expression()?;
+        let brace = self.tokens.consume(TokenKind::LeftBrace, "after match value")?;
+        let mut arms = vec![];
+
+        while !self.check(TokenKind::RightBrace) {
+            let pattern = self.pattern()?;
+
+            let guard = if match_token(self, &TokenKind::If) {
+                Some(self.expression()?)
+            } else {
+                None
+            };
+
+            self.tokens.consume(TokenKind::Arrow, "after match pattern")?;
+            let body = self.expression()?;
+            arms.push(Arm { pattern, guard, body });
+
+            if !match_token(self, &TokenKind::Comma) {
+                break;
+            }
+        }
+
+        let end = self.tokens.consume(TokenKind::RightBrace, "after match arms")
+            .map_err(|err| err.with_label(brace.span, "opening brace here"))?;
+
+        Ok(Expr::Match { keyword: keyword.clone(), value: Box::new(value), arms, span: keyword.span.to(end.span) })
+    }
+
+    fn pattern(&mut self) -> Result<Pattern, ParseError> {
+        let token = self.tokens.peek().unwrap().clone();
+
+        match token.kind {
+            TokenKind::Number | TokenKind::String | TokenKind::True | TokenKind::False | TokenKind::Null => {
+                self.tokens.next();
+                Ok(Pattern::Literal { span: token.span, value: Value::from_token(token) })
+            },
+            // only numbers can be negative
+            TokenKind::Minus if self.tokens.peek_next().is_some_and(|next| next.kind == TokenKind::Number) => {
+                self.tokens.next();
+                let number = self.tokens.next().unwrap();
+                let span = token.span.to(number.span);
+                let value = Value::from_token(number).apply_unary_op(&token).expect("numbers can be negated");
+                Ok(Pattern::Literal { span, value })
+            },
+            TokenKind::Identifier if token.lexeme == "_" => {
+                self.tokens.next();
+                Ok(Pattern::Wildcard { span: token.span })
+            },
+            TokenKind::Identifier if self.tokens.peek_next().is_some_and(|next| next.kind == TokenKind::Dot) => {
+                self.tokens.next();
+                self.tokens.next();
+                self.variant_pattern(token)
+            },
+            TokenKind::Identifier => {
+                self.tokens.next();
+                Ok(Pattern::Binding { span: token.span, name: token })
+            },
+            TokenKind::LeftBracket => {
+                self.tokens.next();
+                let elements = self.patterns(TokenKind::RightBracket)?;
+                let close = self.closing_bracket(&token, "after array pattern")?;
+                Ok(Pattern::Array { elements, span: token.span.to(close.span) })
+            },
+            TokenKind::LeftBrace => {
+                self.tokens.next();
+                self.object_pattern(token)
+            },
+            _ => Err(ParseError::at(ParseErrorKind::ExpectedPattern(describe(&token)), &token))
+        }
+    }
+
+    // patterns separated by commas up to `close`, which is left for the caller
+    fn patterns(&mut self, close: TokenKind) -> Result<Vec<Pattern>, ParseError> {
+        let mut patterns = vec![];
+
+        while !self.check(close.clone()) {
+            patterns.push(self.pattern()?);
+
+            if !match_token(self, &TokenKind::Comma) {
+                break;
+            }
+        }
+        Ok(patterns)
+    }
+
+    // `Enum.Variant` or `Enum.Variant(patterns)`, the `Enum.` is already consumed
+    fn variant_pattern(&mut self, enumeration: Token) -> Result<Pattern, ParseError> {
+        let variant = Box::new(self.tokens.consume(TokenKind::Identifier, "as variant name")?);
+
+        if !match_token(self, &TokenKind::LeftParen) {
+            return Ok(Pattern::Variant { span: enumeration.span.to(variant.span), enumeration, variant, fields: None });
+        }
+
+        let fields = self.patterns(TokenKind::RightParen)?;
+        let close = self.tokens.consume(TokenKind::RightParen, "after variant fields")?;
+        Ok(Pattern::Variant { span: enumeration.span.to(close.span), enumeration, variant, fields: Some(fields) })
+    }
+
+    // `{ name, field: pattern }`, the `{` is already consumed
+    fn object_pattern(&mut self, brace: Token) -> Result<Pattern, ParseError> {
+        let mut fields = vec![];
+
+        while !self.check(TokenKind::RightBrace) {
+            let name = self.tokens.consume(TokenKind::Identifier, "as field name")?;
+
+            // `{ name }` is short for `{ name: name }`
+            let pattern = if match_token(self, &TokenKind::Colon) {
+                self.pattern()?
+            } else {
+                Pattern::Binding { span: name.span, name: name.clone() }
+            };
+            fields.push((name, pattern));
+
+            if !match_token(self, &TokenKind::Comma) {
+                break;
+            }
+        }
+
+        let end = self.tokens.consume(TokenKind::RightBrace, "after object pattern")
+            .map_err(|err| err.with_label(brace.span, "opening brace here"))?;
+        Ok(Pattern::Object { fields, span: brace.span.to(end.span) })
+    }
+
+    // `enum Name { Variant, Variant(field, field), ... }`, a trailing comma is allowed, the `enum`
+    // is already consumed
+    fn enum_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let keyword = self.tokens.previous().unwrap();
+
+        let name = self.tokens.consume(TokenKind::Identifier, "as enum name")?;
+        let brace = self.tokens.consume(TokenKind::LeftBrace, "before enum variants")?;
+        let mut variants = vec![];
+
+        while !self.check(TokenKind::RightBrace) {
+            let variant = self.tokens.consume(TokenKind::Identifier, "as variant name")?;
+
+            let fields = if match_token(self, &TokenKind::LeftParen) {
+                self.parameters(&variant.lexeme)?
+            } else {
+                vec![]
+            };
+            variants.push((variant, fields));
+
+            if !match_token(self, &TokenKind::Comma) {
+                break;
+            }
+        }
+
+        let end = self.tokens.consume(TokenKind::RightBrace, "after enum variants")
+            .map_err(|err| err.with_label(brace.span, "opening brace here"))?;
+
+        Ok(Stmt::Enum { name, variants, span: keyword.span.to(end.span) })
+    }
+
+    // `fn (a, b) { ... }`, the `fn` is already consumed
+    fn function_expression(&mut self) -> Result<Expr, ParseError> {
+        let keyword = self.tokens.previous().unwrap();
+
+        self.tokens.consume(TokenKind::LeftParen, "after 'fn'")?;
+        let params = self.parameters("anonymous")?;
+
+        self.tokens.consume(TokenKind::LeftBrace, "before function body")?;
+        let (body, body_span) = self.function_body(|parser| parser.block_statement())?;
+
+        Ok(Expr::Function { params, body, span: keyword.span.to(body_span) })
+    }
+
+    // `(a, b) => a + b` or `(a, b) => { ... }`, the `(` is already consumed
+    fn arrow_function(&mut self) -> Result<Expr, ParseError> {
+        let paren = self.tokens.previous().unwrap();
+
+        let params = self.parameters("anonymous")?;
+        self.tokens.consume(TokenKind::Arrow, "after parameters")?;
+
+        let (body, body_span) = if match_token(self, &TokenKind::LeftBrace) {
+            self.function_body(|parser| parser.block_statement())?
+        } else {
+            // an expression body is returned as if it was `{ return expr; }`
+            self.function_body(|parser| {
+                let value = parser.expression()?;
+                Ok(Stmt::Return { span: value.span(), value: Some(value) })
+            })?
+        };
+
+        Ok(Expr::Function { params, body, span: paren.span.to(body_span) })
+    }
+
+    // parameters up to and including the `)`, the `(` is already consumed
+    fn parameters(&mut self, function_name: &str) -> Result<Vec<Token>, ParseError> {
         let mut params = vec![];
 
         if !self.check(TokenKind::RightParen) {
             loop {
                 if params.len() >= 255 {
-                    return Err(format!("More than 255 parameters in function {:?}", name))
+                    return Err(ParseError::at(ParseErrorKind::TooManyParameters(function_name.to_string()), self.tokens.peek().unwrap()))
                 }
     
                 let param = self.tokens
-                    .consume(TokenKind::Identifier, "Expected parameter name")?;
+                    .consume(TokenKind::Identifier, "as parameter name")?;
     
                 params.push(param);
     
@@ -214,46 +940,64 @@ impl Parser {
             }
         }
 
-        self.tokens.consume(TokenKind::RightParen, format!("Expected ')' after parameters in line {} column {}",
-            self.tokens.peek().unwrap().line_number,
-            self.tokens.peek().unwrap().column_number,
-        ).as_str())?;
-
-        self.tokens.consume(TokenKind::RightParen, format!("Expected '{{' after parameters in line {} column {}",
-            self.tokens.peek().unwrap().line_number,
-            self.tokens.peek().unwrap().column_number,
-        ).as_str())?;
+        self.tokens.consume(TokenKind::RightParen, "after parameters")?;
+        Ok(params)
+    }
 
-        let body = match self.block_statement()? {
-            Stmt::Block { statements } => statements,
-            _ => panic!("Block statement parsed something that wasnt a block")
-        };
+    // runs `parse` as a function body, which starts outside of any loop even when the function is
+    // written inside one, and gives back its statements
+    fn function_body(&mut self, parse: impl FnOnce(&mut Self) -> Result<Stmt, ParseError>) -> Result<(Rc<[Stmt]>, Span), ParseError> {
+        let enclosing_loops = std::mem::take(&mut self.loops);
+        self.function_depth += 1;
+        let body = parse(self);
+        self.function_depth -= 1;
+        self.loops = enclosing_loops;
 
-        Ok(Stmt::Function { 
-            name,
-            params,
-            body 
-        })
+        match body? {
+            Stmt::Block { statements, span } => Ok((statements.into(), span)),
+            other => {
+                let span = other.span();
+                Ok((Rc::new([other]), span))
+            },
+        }
     }
 
-    fn function_expression(&mut self) {
-        
+    // whether the `(` at the current token starts the parameters of `(a, b) => ...`
+    fn is_arrow_function(&self) -> bool {
+        let mut offset = 1;
+
+        if self.tokens.peek_at(offset).is_some_and(|token| token.kind == TokenKind::Identifier) {
+            loop {
+                offset += 1;
+                match self.tokens.peek_at(offset).map(|token| &token.kind) {
+                    Some(TokenKind::Comma) if self.tokens.peek_at(offset + 1).is_some_and(|token| token.kind == TokenKind::Identifier) => offset += 1,
+                    Some(TokenKind::RightParen) => break,
+                    _ => return false,
+                }
+            }
+        }
+
+        self.tokens.peek_at(offset).is_some_and(|token| token.kind == TokenKind::RightParen)
+            && self.tokens.peek_at(offset + 1).is_some_and(|token| token.kind == TokenKind::Arrow)
     }
 
     fn check(&mut self, typ: TokenKind) -> bool {
         self.tokens.peek().unwrap().kind == typ
     }
 
-    fn block_statement(&mut self) -> Result<Stmt, String> {
-        let mut statements: Vec<Box<Stmt>> = vec![];
+    // the `{` is already consumed
+    fn block_statement(&mut self) -> Result<Stmt, ParseError> {
+        let brace = self.tokens.previous().unwrap();
+        let mut statements: Vec<Stmt> = vec![];
 
         while !self.check(TokenKind::RightBrace) && !self.tokens.is_eof() {
             let decl: Stmt = self.declaration()?;
-            statements.push(Box::new(decl));
+            statements.push(decl);
         }
 
-        self.tokens.consume(TokenKind::RightBrace, "Expected '}' after a block")?;
-        Ok(Stmt::Block { statements })
+        let end = self.tokens.consume(TokenKind::RightBrace, "after block")
+            .map_err(|err| err.with_label(brace.span, "opening brace here"))?;
+        Ok(Stmt::Block { statements, span: brace.span.to(end.span) })
     }
 
     // this what it does it to look for the next keyword after and error to keep looking normally from there
@@ -278,4 +1022,610 @@ impl Parser {
         }
     }
 
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{interpreter::environment::Environment, lexer::{lexer::Lexer, tokens::Span}};
+
+    fn parse_expression(source: &str) -> Expr {
+        let tokens = Lexer::new("filename", source.to_string()).tokenize().unwrap();
+        let mut stmts = Parser::new(tokens).parse().unwrap();
+
+        match stmts.remove(0) {
+            Stmt::Expression { expression, .. } => expression,
+            _ => panic!("Expected an expression statement")
+        }
+    }
+
+    #[test]
+    fn expression_spans() {
+        let tokens = Lexer::new("filename", "-(1);\n\"a ${2}\";".to_string()).tokenize().unwrap();
+        let stmts = Parser::new(tokens).parse().unwrap();
+
+        assert_eq!(stmts[0].span(), Span::new(0, 5));
+        assert_eq!(stmts[1].span(), Span::new(6, 15));
+
+        match &stmts[0] {
+            Stmt::Expression { expression: Expr::Unary { right, .. }, .. } => assert_eq!(right.span(), Span::new(1, 4)),
+            _ => panic!("Expected a unary expression")
+        }
+        match &stmts[1] {
+            Stmt::Expression { expression, .. } => assert_eq!(expression.span(), Span::new(6, 14)),
+            _ => panic!("Expected an expression statement")
+        }
+    }
+
+    #[test]
+    fn block_span() {
+        let tokens = Lexer::new("filename", "{ 1; { 2; } }".to_string()).tokenize().unwrap();
+        let stmts = Parser::new(tokens).parse().unwrap();
+
+        match &stmts[0] {
+            Stmt::Block { statements, span } => {
+                assert_eq!(*span, Span::new(0, 13));
+                assert_eq!(statements[1].span(), Span::new(5, 11));
+            },
+            _ => panic!("Expected a block")
+        }
+    }
+
+    // renders an expression fully parenthesized, to check how it was grouped
+    fn sexpr(expr: &Expr) -> String {
+        match expr {
+            Expr::Literal { value, .. } => value.to_string(),
+            Expr::Grouping { expression, .. } => format!("(group {})", sexpr(expression)),
+            Expr::Binary { left, operator, right, .. } => format!("({} {} {})", operator.lexeme, sexpr(left), sexpr(right)),
+            Expr::Unary { operator, right, .. } => format!("({} {})", operator.lexeme, sexpr(right)),
+            Expr::Conditional { condition, then_branch, else_branch, .. } => {
+                format!("(? {} {} {})", sexpr(condition), sexpr(then_branch), sexpr(else_branch))
+            },
+            Expr::Call { callee, arguments, optional, .. } => {
+                let arguments: Vec<String> = arguments.iter().map(sexpr).collect();
+                format!("({} {} {})", if *optional { "call?" } else { "call" }, sexpr(callee), arguments.join(" "))
+            },
+            Expr::Get { object, name, optional, .. } => {
+                format!("({} {} {})", if *optional { "?." } else { "." }, sexpr(object), name.lexeme)
+            },
+            Expr::This { .. } => "self".to_string(),
+            Expr::Match { value, arms, .. } => {
+                let arms: Vec<String> = arms.iter().map(|arm| match &arm.guard {
+                    Some(guard) => format!("[{} if {} => {}]", arm.pattern, sexpr(guard), sexpr(&arm.body)),
+                    None => format!("[{} => {}]", arm.pattern, sexpr(&arm.body)),
+                }).collect();
+                format!("(match {} {})", sexpr(value), arms.join(" "))
+            },
+            Expr::Super { method, .. } => format!("(super {})", method.lexeme),
+            Expr::Interpolation { parts, .. } => {
+                let parts: Vec<String> = parts.iter().map(sexpr).collect();
+                format!("(interpolation {})", parts.join(" "))
+            },
+            Expr::Variable { name, .. } => name.lexeme.clone(),
+            Expr::Assign { target, operator, value, .. } => format!("({} {} {})", operator.lexeme, sexpr(target), sexpr(value)),
+            Expr::Array { elements, .. } => {
+                let elements: Vec<String> = elements.iter().map(sexpr).collect();
+                format!("[{}]", elements.join(" "))
+            },
+            Expr::Index { object, index, .. } => format!("(index {} {})", sexpr(object), sexpr(index)),
+            Expr::Object { properties, .. } => {
+                let properties: Vec<String> = properties.iter().map(|(key, value)| match key {
+                    Key::Name(name) => format!("{:?}: {}", name, sexpr(value)),
+                    Key::Computed { key, .. } => format!("[{}]: {}", sexpr(key), sexpr(value)),
+                }).collect();
+                format!("{{{}}}", properties.join(" "))
+            },
+            Expr::Slice { object, start, end, .. } => {
+                let bound = |bound: &Option<Box<Expr>>| bound.as_ref().map_or("_".to_string(), |bound| sexpr(bound));
+                format!("(slice {} {} {})", sexpr(object), bound(start), bound(end))
+            },
+            Expr::Function { params, body, .. } => {
+                let params: Vec<&str> = params.iter().map(|param| param.lexeme.as_str()).collect();
+                format!("(fn ({}) {} statements)", params.join(" "), body.len())
+            },
+        }
+    }
+
+    fn parse_sexpr(source: &str) -> String {
+        sexpr(&parse_expression(source))
+    }
+
+    #[test]
+    fn factor_level() {
+        assert_eq!(parse_sexpr("8 / 4 * 2 % 3;"), "(% (* (/ 8 4) 2) 3)");
+        assert_eq!(parse_expression("7 % 4;").evaluate(&Environment::new()), Ok(Value::Number(3.0)));
+    }
+
+    #[test]
+    fn term_level() {
+        assert_eq!(parse_sexpr("1 - 2 + 3;"), "(+ (- 1 2) 3)");
+        assert_eq!(parse_sexpr("1 + 2 * 3 - 4 / 2;"), "(- (+ 1 (* 2 3)) (/ 4 2))");
+        assert_eq!(parse_expression("10 - 4 - 3;").evaluate(&Environment::new()), Ok(Value::Number(3.0)));
+    }
+
+    #[test]
+    fn comparison_level() {
+        assert_eq!(parse_sexpr("1 + 1 < 3 * 1;"), "(< (+ 1 1) (* 3 1))");
+        assert_eq!(parse_sexpr("1 >= 2 <= 3;"), "(<= (>= 1 2) 3)");
+    }
+
+    #[test]
+    fn equality_level() {
+        assert_eq!(parse_sexpr("1 < 2 == 3 > 4;"), "(== (< 1 2) (> 3 4))");
+        assert_eq!(parse_sexpr("1 == 2 != true;"), "(!= (== 1 2) true)");
+        assert_eq!(parse_expression("1 + 2 == 3;").evaluate(&Environment::new()), Ok(Value::Boolean(true)));
+    }
+
+    #[test]
+    fn and_level() {
+        assert_eq!(parse_sexpr("1 == 1 and 2 != 3 and true;"), "(and (and (== 1 1) (!= 2 3)) true)");
+    }
+
+    #[test]
+    fn or_level() {
+        assert_eq!(parse_sexpr("true or false and false;"), "(or true (and false false))");
+        assert_eq!(parse_sexpr("true and false or true;"), "(or (and true false) true)");
+        assert_eq!(parse_expression("false or 1 < 2;").evaluate(&Environment::new()), Ok(Value::Boolean(true)));
+    }
+
+    #[test]
+    fn unary_and_grouping_bind_tighter() {
+        assert_eq!(parse_sexpr("-1 * (2 + 3);"), "(* (- 1) (group (+ 2 3)))");
+        assert_eq!(parse_sexpr("!true == false;"), "(== (! true) false)");
+    }
+
+    #[test]
+    fn parse_else_if_chain() {
+        let tokens = Lexer::new("filename", "if (a) 1; else if (b) { 2; } else 3;".to_string()).tokenize().unwrap();
+        let stmts = Parser::new(tokens).parse().unwrap();
+
+        match &stmts[0] {
+            Stmt::If { condition, else_branch: Some(else_branch), span, .. } => {
+                assert_eq!(sexpr(condition), "a");
+                assert!(matches!(**else_branch, Stmt::If { else_branch: Some(_), .. }));
+                assert_eq!(*span, Span::new(0, 36));
+            },
+            _ => panic!("Expected an if with an else branch")
+        }
+    }
+
+    #[test]
+    fn parse_for_with_empty_clauses() {
+        let tokens = Lexer::new("filename", "for (;;) {}".to_string()).tokenize().unwrap();
+        let stmts = Parser::new(tokens).parse().unwrap();
+
+        assert!(matches!(&stmts[0], Stmt::For { initializer: None, condition: None, increment: None, .. }));
+    }
+
+    #[test]
+    fn while_needs_parenthesis() {
+        let errors = parse_errors("while x {}");
+
+        assert_eq!(errors[0].to_string(), "Expected '(' after 'while', found 'x' in line 1 column 8");
+    }
+
+    #[test]
+    fn break_outside_loop() {
+        let errors = parse_errors("if (true) { break; }");
+
+        assert_eq!(errors[0].kind, ParseErrorKind::OutsideLoop("break".to_string()));
+        assert_eq!(errors[0].span, Span::new(12, 17));
+    }
+
+    #[test]
+    fn continue_with_unknown_label() {
+        let errors = parse_errors("outer: while (true) { inner: for (;;) { continue other; } }");
+
+        assert_eq!(errors[0].to_string(), "No enclosing loop is labelled 'other' in line 1 column 55");
+    }
+
+    #[test]
+    fn label_needs_a_loop() {
+        let errors = parse_errors("outer: { break outer; }");
+
+        assert_eq!(errors[0].kind, ParseErrorKind::LabelWithoutLoop("outer".to_string()));
+    }
+
+    #[test]
+    fn labelled_loop_span() {
+        let tokens = Lexer::new("filename", "outer: while (true) { break outer; }".to_string()).tokenize().unwrap();
+        let stmts = Parser::new(tokens).parse().unwrap();
+
+        match &stmts[0] {
+            Stmt::While { label: Some(label), span, .. } => {
+                assert_eq!(label.lexeme, "outer");
+                assert_eq!(*span, Span::new(0, 36));
+            },
+            _ => panic!("Expected a labelled while")
+        }
+    }
+
+    #[test]
+    fn parse_function_declaration() {
+        let tokens = Lexer::new("filename", "fn add(a, b) { return a + b; }".to_string()).tokenize().unwrap();
+        let stmts = Parser::new(tokens).parse().unwrap();
+
+        match &stmts[0] {
+            Stmt::Function { name, params, body, span } => {
+                assert_eq!(name.lexeme, "add");
+                assert_eq!(params.iter().map(|param| param.lexeme.as_str()).collect::<Vec<_>>(), vec!["a", "b"]);
+                assert!(matches!(body[0], Stmt::Return { value: Some(_), .. }));
+                assert_eq!(*span, Span::new(0, 30));
+            },
+            _ => panic!("Expected a function declaration")
+        }
+    }
+
+    #[test]
+    fn function_needs_a_body() {
+        let errors = parse_errors("fn f() return 1;");
+
+        assert_eq!(errors[0].to_string(), "Expected '{' before function body, found 'return' in line 1 column 14");
+    }
+
+    #[test]
+    fn return_outside_function() {
+        let errors = parse_errors("while (true) { return 1; }");
+
+        assert_eq!(errors[0].kind, ParseErrorKind::ReturnOutsideFunction);
+    }
+
+    #[test]
+    fn parse_class_declaration() {
+        let source = "class Point { fn init(x) { self.x = x; } fn len() { return self.x; } }";
+        let tokens = Lexer::new("filename", source.to_string()).tokenize().unwrap();
+        let stmts = Parser::new(tokens).parse().unwrap();
+
+        match &stmts[0] {
+            Stmt::Class { name, superclass: None, methods, span } => {
+                assert_eq!(name.lexeme, "Point");
+                let names: Vec<&str> = methods.iter().map(|method| match method {
+                    Stmt::Function { name, .. } => name.lexeme.as_str(),
+                    _ => panic!("Expected a method")
+                }).collect();
+                assert_eq!(names, vec!["init", "len"]);
+                assert_eq!(*span, Span::new(0, source.len()));
+            },
+            _ => panic!("Expected a class declaration")
+        }
+    }
+
+    #[test]
+    fn class_body_only_has_methods() {
+        let errors = parse_errors("class Point { let x = 1; }");
+
+        assert_eq!(errors[0].to_string(), "Expected 'fn' before method, found 'let' in line 1 column 18");
+    }
+
+    #[test]
+    fn parse_subclass() {
+        for source in ["class B < A { fn f() { return super.f; } }", "class B : A { fn f() { return super.f; } }"] {
+            let tokens = Lexer::new("filename", source.to_string()).tokenize().unwrap();
+            let stmts = Parser::new(tokens).parse().unwrap();
+
+            match &stmts[0] {
+                Stmt::Class { superclass: Some(superclass), methods, .. } => {
+                    assert_eq!(superclass.lexeme, "A");
+                    match &methods[0] {
+                        Stmt::Function { body, .. } => match &body[0] {
+                            Stmt::Return { value: Some(value), .. } => assert_eq!(sexpr(value), "(super f)"),
+                            _ => panic!("Expected a return")
+                        },
+                        _ => panic!("Expected a method")
+                    }
+                },
+                _ => panic!("Expected a subclass")
+            }
+        }
+    }
+
+    #[test]
+    fn super_outside_subclass() {
+        for source in ["class A { fn f() { super.f(); } }", "super.f();"] {
+            let errors = parse_errors(source);
+
+            assert_eq!(errors[0].kind, ParseErrorKind::SuperOutsideSubclass);
+            assert_eq!(errors[0].code(), "P0011");
+        }
+        assert_eq!(parse_errors("class B < A { fn f() { super(); } }")[0].to_string(), "Expected '.' after 'super', found '(' in line 1 column 30");
+
+        let errors = parse_errors("class E { fn x() { return super.x(); } }");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, ParseErrorKind::SuperOutsideSubclass);
+    }
+
+    #[test]
+    fn parse_enum_declaration() {
+        let source = "enum Shape { Circle(r), Rect(w, h), Empty, }";
+        let tokens = Lexer::new("filename", source.to_string()).tokenize().unwrap();
+        let stmts = Parser::new(tokens).parse().unwrap();
+
+        match &stmts[0] {
+            Stmt::Enum { name, variants, span } => {
+                assert_eq!(name.lexeme, "Shape");
+                let variants: Vec<(&str, usize)> = variants.iter()
+                    .map(|(variant, fields)| (variant.lexeme.as_str(), fields.len()))
+                    .collect();
+                assert_eq!(variants, vec![("Circle", 1), ("Rect", 2), ("Empty", 0)]);
+                assert_eq!(*span, Span::new(0, source.len()));
+            },
+            _ => panic!("Expected an enum declaration")
+        }
+    }
+
+    #[test]
+    fn unclosed_enum() {
+        let errors = parse_errors("enum Shape { Circle(r) Empty }");
+
+        assert_eq!(errors[0].to_string(), "Expected '}' after enum variants, found 'Empty' in line 1 column 29");
+        assert_eq!(errors[0].labels, vec![(Span::new(11, 12), "opening brace here".to_string())]);
+    }
+
+    #[test]
+    fn parse_match() {
+        assert_eq!(
+            parse_sexpr(r#"match v { 0 => "zero", -1 => "minus one", [a, b] => a, { name, age: 1 } => name, Shape.Circle(r) if r > 1 => r, Shape.Empty => null, _ => v, };"#),
+            r#"(match v [0 => zero] [-1 => minus one] [[a, b] => a] [{ name, age: 1 } => name] [Shape.Circle(r) if (> r 1) => r] [Shape.Empty => null] [_ => v])"#
+        );
+    }
+
+    #[test]
+    fn match_errors() {
+        let errors = parse_errors("let x = match v { 1 + 1 => 2 };");
+        assert_eq!(errors[0].to_string(), "Expected '=>' after match pattern, found '+' in line 1 column 22");
+
+        let errors = parse_errors("let x = match v { (a) => 2 };");
+        assert_eq!(errors[0].kind, ParseErrorKind::ExpectedPattern("'('".to_string()));
+        assert_eq!(errors[0].code(), "P0012");
+
+        let errors = parse_errors("let x = match v { _ => 1 _ => 2 };");
+        assert_eq!(errors[0].to_string(), "Expected '}' after match arms, found '_' in line 1 column 27");
+        assert_eq!(errors[0].labels, vec![(Span::new(16, 17), "opening brace here".to_string())]);
+    }
+
+    #[test]
+    fn self_outside_class() {
+        let errors = parse_errors("fn f() { return self; }");
+
+        assert_eq!(errors[0].kind, ParseErrorKind::SelfOutsideClass);
+        assert_eq!(errors[0].code(), "P0010");
+
+        let errors = parse_errors("fn g() { return self.x; } let y = ;");
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].kind, ParseErrorKind::SelfOutsideClass);
+        assert_eq!(errors[1].kind, ParseErrorKind::ExpectedExpression("';'".to_string()));
+    }
+
+    #[test]
+    fn break_inside_function_inside_loop() {
+        let errors = parse_errors("while (true) { fn f() { break; } }");
+
+        assert_eq!(errors[0].kind, ParseErrorKind::OutsideLoop("break".to_string()));
+    }
+
+    #[test]
+    fn parse_function_expressions() {
+        assert_eq!(parse_sexpr("fn (a, b) { let c = a; return c + b; };"), "(fn (a b) 2 statements)");
+        assert_eq!(parse_sexpr("(a, b) => a + b;"), "(fn (a b) 1 statements)");
+        assert_eq!(parse_sexpr("() => { };"), "(fn () 0 statements)");
+        assert_eq!(parse_sexpr("apply((x) => x * 2, 3);"), "(call apply (fn (x) 1 statements) 3)");
+    }
+
+    #[test]
+    fn arrow_body_returns_the_expression() {
+        match parse_expression("(x) => x + 1;") {
+            Expr::Function { body, span, .. } => {
+                match &body[0] {
+                    Stmt::Return { value: Some(value), .. } => assert_eq!(sexpr(value), "(+ x 1)"),
+                    _ => panic!("Expected the body to return the expression")
+                }
+                assert_eq!(span, Span::new(0, 12));
+            },
+            _ => panic!("Expected a function expression")
+        }
+    }
+
+    #[test]
+    fn grouping_is_not_an_arrow() {
+        assert_eq!(parse_sexpr("(a) + (b);"), "(+ (group a) (group b))");
+    }
+
+    #[test]
+    fn null_coalescing_binds_loosest() {
+        assert_eq!(parse_sexpr("a ?? b or c ?? d;"), "(?? (?? a (or b c)) d)");
+        assert_eq!(parse_sexpr("x = a ?? 1;"), "(= x (?? a 1))");
+    }
+
+    #[test]
+    fn optional_chaining() {
+        assert_eq!(parse_sexpr("user?.address.city;"), "(. (?. user address) city)");
+        assert_eq!(parse_sexpr("callback?.(1)?.done;"), "(?. (call? callback 1) done)");
+        assert_eq!(parse_expression("a?.b;").span(), Span::new(0, 4));
+    }
+
+    #[test]
+    fn property_needs_a_name() {
+        let errors = parse_errors("a?.1;");
+
+        assert_eq!(errors[0].to_string(), "Expected identifier as property name after '?.', found '1' in line 1 column 5");
+    }
+
+    #[test]
+    fn parse_arrays() {
+        assert_eq!(parse_sexpr("[1, 2 + 3, [],];"), "[1 (+ 2 3) []]");
+        assert_eq!(parse_sexpr("a[0][-1] + b[i + 1];"), "(+ (index (index a 0) (- 1)) (index b (+ i 1)))");
+        assert_eq!(parse_sexpr("a[1:3];"), "(slice a 1 3)");
+        assert_eq!(parse_sexpr("a[:n][2:];"), "(slice (slice a _ n) 2 _)");
+        assert_eq!(parse_sexpr("a[0][1] += 2;"), "(+= (index (index a 0) 1) 2)");
+    }
+
+    #[test]
+    fn unclosed_array() {
+        let errors = parse_errors("let a = [1, 2;");
+
+        assert_eq!(errors[0].to_string(), "Expected ']' after array elements, found ';' in line 1 column 15");
+        assert_eq!(errors[0].labels, vec![(Span::new(8, 9), "opening bracket here".to_string())]);
+    }
+
+    #[test]
+    fn slices_cannot_be_assigned() {
+        let errors = parse_errors("a[1:2] = [];");
+
+        assert_eq!(errors[0].kind, ParseErrorKind::InvalidAssignmentTarget("=".to_string()));
+    }
+
+    #[test]
+    fn parse_objects() {
+        assert_eq!(parse_sexpr("x = { name: \"x\", \"quoted key\": 1, [k + 1]: 2, name, };"),
+            "(= x {\"name\": x \"quoted key\": 1 [(+ k 1)]: 2 \"name\": name})");
+        assert_eq!(parse_sexpr("x = {}.a;"), "(= x (. {} a))");
+        assert_eq!(parse_sexpr("o.a.b = o[\"c\"];"), "(= (. (. o a) b) (index o c))");
+    }
+
+    #[test]
+    fn objects_and_blocks_in_statement_position() {
+        let statement = |source: &str| {
+            let tokens = Lexer::new("filename", source.to_string()).tokenize().unwrap();
+            Parser::new(tokens).parse().unwrap().remove(0)
+        };
+
+        assert!(matches!(statement("{ a: 1 };"), Stmt::Expression { .. }));
+        assert!(matches!(statement("{ a, b };"), Stmt::Expression { .. }));
+        assert!(matches!(statement("{ [k]: 1 };"), Stmt::Expression { .. }));
+        assert!(matches!(statement("{ [1, 2]; }"), Stmt::Block { .. }));
+        assert!(matches!(statement("{ a; }"), Stmt::Block { .. }));
+        assert!(matches!(statement("{ outer: while (true) break outer; }"), Stmt::Block { .. }));
+        assert!(matches!(statement("{}"), Stmt::Block { .. }));
+    }
+
+    #[test]
+    fn expected_property_name() {
+        let errors = parse_errors("let o = { 1: 2 };");
+
+        assert_eq!(errors[0].to_string(), "Expected property name, found '1' in line 1 column 12");
+    }
+
+    #[test]
+    fn optional_property_cannot_be_assigned() {
+        let errors = parse_errors("a?.b = 1;");
+
+        assert_eq!(errors[0].kind, ParseErrorKind::InvalidAssignmentTarget("=".to_string()));
+    }
+
+    #[test]
+    fn conditional_binds_looser_than_or() {
+        assert_eq!(parse_sexpr("a or b ? c and d : e ?? f;"), "(? (or a b) (and c d) (?? e f))");
+        assert_eq!(parse_sexpr("x = a ? 1 : 2;"), "(= x (? a 1 2))");
+    }
+
+    #[test]
+    fn nested_conditionals_are_right_associative() {
+        assert_eq!(parse_sexpr("a ? b : c ? d : e;"), "(? a b (? c d e))");
+        assert_eq!(parse_sexpr("a ? b ? c : d : e;"), "(? a (? b c d) e)");
+        assert_eq!(parse_sexpr("a ? b : c ? d : e ? f : g;"), "(? a b (? c d (? e f g)))");
+        assert_eq!(parse_sexpr("(a ? b : c) ? d : e;"), "(? (group (? a b c)) d e)");
+    }
+
+    #[test]
+    fn conditional_inside_index_and_object() {
+        assert_eq!(parse_sexpr("l[a ? 0 : 1];"), "(index l (? a 0 1))");
+        assert_eq!(parse_sexpr("x = { k: a ? b : c };"), "(= x {\"k\": (? a b c)})");
+    }
+
+    #[test]
+    fn conditional_span() {
+        assert_eq!(parse_expression("a ? b : c;").span(), Span::new(0, 9));
+    }
+
+    #[test]
+    fn conditional_needs_colon() {
+        let errors = parse_errors("a ? b;");
+
+        assert_eq!(errors[0].to_string(), "Expected ':' after the first branch of '?', found ';' in line 1 column 7");
+        assert_eq!(errors[0].labels, vec![(Span::new(2, 3), "conditional starts here".to_string())]);
+    }
+
+    #[test]
+    fn parse_let() {
+        let tokens = Lexer::new("filename", "let x = a + 1; let y;".to_string()).tokenize().unwrap();
+        let stmts = Parser::new(tokens).parse().unwrap();
+
+        match &stmts[0] {
+            Stmt::Let { name, initializer: Some(initializer), span } => {
+                assert_eq!(name.lexeme, "x");
+                assert_eq!(sexpr(initializer), "(+ a 1)");
+                assert_eq!(*span, Span::new(0, 14));
+            },
+            _ => panic!("Expected a let with an initializer")
+        }
+        assert!(matches!(&stmts[1], Stmt::Let { initializer: None, .. }));
+    }
+
+    #[test]
+    fn let_needs_a_name() {
+        let errors = parse_errors("let = 1;");
+
+        assert_eq!(errors[0].to_string(), "Expected identifier as variable name, found '=' in line 1 column 6");
+    }
+
+    #[test]
+    fn assignment_is_right_associative() {
+        assert_eq!(parse_sexpr("a = b = 1 + 2;"), "(= a (= b (+ 1 2)))");
+        assert_eq!(parse_sexpr("a += b *= 2;"), "(+= a (*= b 2))");
+        assert_eq!(parse_sexpr("a = b or c;"), "(= a (or b c))");
+    }
+
+    #[test]
+    fn assignment_span() {
+        assert_eq!(parse_expression("total -= 1 + 2;").span(), Span::new(0, 14));
+    }
+
+    #[test]
+    fn invalid_assignment_target() {
+        let errors = parse_errors("1 + 2 = 3;");
+
+        assert_eq!(errors[0].kind, ParseErrorKind::InvalidAssignmentTarget("=".to_string()));
+        assert_eq!(errors[0].span, Span::new(0, 5));
+        assert_eq!(errors[0].code(), "P0004");
+    }
+
+    #[test]
+    fn binary_span() {
+        assert_eq!(parse_expression("1 + 2 * 3;").span(), Span::new(0, 9));
+    }
+
+    fn parse_errors(source: &str) -> Vec<ParseError> {
+        let tokens = Lexer::new("filename", source.to_string()).tokenize().unwrap();
+        Parser::new(tokens).parse().err().expect("Expected parse errors")
+    }
+
+    #[test]
+    fn expected_token_error() {
+        let errors = parse_errors("(1;");
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, ParseErrorKind::ExpectedToken {
+            expected: TokenKind::RightParen,
+            found: "';'".to_string(),
+            context: "after expression".to_string()
+        });
+        assert_eq!(errors[0].span, Span::new(2, 3));
+    }
+
+    #[test]
+    fn errors_are_collected() {
+        let errors = parse_errors("1 2; ;");
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].to_string(), "Expected ';' after expression, found '2' in line 1 column 4");
+        assert_eq!(errors[1].kind, ParseErrorKind::ExpectedExpression("';'".to_string()));
+    }
+
+    #[test]
+    fn parse_interpolation() {
+        let expr = parse_expression(r#""a ${"b ${1}"} ${true}!";"#);
+
+        assert!(matches!(expr, Expr::Interpolation { .. }));
+        assert_eq!(expr.evaluate(&Environment::new()), Ok(Value::String("a b 1 true!".to_string())));
+    }
 }
\ No newline at end of file
@@ -1,6 +1,6 @@
 use crate::lexer::tokens::{Token, TokenKind};
 
-use super::parser::Parser;
+use super::{error::{describe, ParseError, ParseErrorKind}, parser::Parser};
 
 #[derive(Debug)]
 pub struct TokenStream {
@@ -12,7 +12,7 @@ impl TokenStream {
     /// Creates a new TokenStream from a generic collection.
     pub fn new(input: Vec<Token>) -> Self {
         TokenStream {
-            input: input,  // Convert the String into a Vec<Token>
+            input,  // Convert the String into a Vec<Token>
             position: 0,
         }
     }
@@ -27,8 +27,13 @@ impl TokenStream {
         self.input.get(self.position + 1)
     }
 
+    /// Peeks `offset` elements ahead of the current one without consuming.
+    pub fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.input.get(self.position + offset)
+    }
+
     pub fn previous(&self) -> Option<Token> {
-        if self.position >= 0 {
+        if self.position > 0 {
             let previous = self.input[self.position - 1].clone();
             Some(previous)
         } else {
@@ -47,20 +52,25 @@ impl TokenStream {
         }
     }
 
-    pub fn consume(&mut self, token_kind: TokenKind, msg: &str) -> Result<Token, String> {
-        let token = self.peek();
-        if token.unwrap().kind == token_kind {
+    /// Consumes the current token if it is of `token_kind`, `context` tells where it was expected for the error.
+    pub fn consume(&mut self, token_kind: TokenKind, context: &str) -> Result<Token, ParseError> {
+        let token = self.peek().unwrap();
+        if token.kind == token_kind {
             self.next();
             let token = self.previous();
             Ok(token.unwrap())
         } else {
-            Err(msg.to_string())
+            Err(ParseError::at(ParseErrorKind::ExpectedToken {
+                expected: token_kind,
+                found: describe(token),
+                context: context.to_string(),
+            }, token))
         }
     }
 
-    /// Checks if the stream has reached the end.
+    /// Checks if the stream has reached the EOF token (or ran past the end).
     pub fn is_eof(&self) -> bool {
-        self.position >= self.input.len()
+        self.peek().is_none_or(|token| token.kind == TokenKind::EOF)
     }
 }
 
@@ -1,19 +1,964 @@
-use crate::{ast::expr::Expr, lexer::tokens::Token};
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::{
+    ast::{class::{Class, Method}, enumeration::{Enum, Variant}, error::{RuntimeError, RuntimeErrorKind}, expr::Expr, value::Value},
+    interpreter::environment::Environment,
+    lexer::tokens::{Span, Token},
+};
+
+/// How a statement finished, so a loop can react to a `break` or `continue` from deep in its body.
+#[derive(Debug, PartialEq)]
+pub enum Flow {
+    Normal,
+    Break(Option<String>), // the label it targets, if any
+    Continue(Option<String>),
+    Return(Value),
+}
 
 pub enum Stmt {
     Expression {
-        expression: Expr
+        expression: Expr,
+        span: Span
     },
     Let {
         name: Token,
-        initializer: Expr
+        initializer: Option<Expr>, // `let x;` has none and starts as null
+        span: Span
     },
     Function {
         name: Token,
         params: Vec<Token>,
-        body: Vec<Box<Stmt>>
+        body: Rc<[Stmt]>, // shared with the callables made from it
+        span: Span
+    },
+    Class {
+        name: Token,
+        superclass: Option<Token>, // the name after `<` or `:`
+        methods: Vec<Stmt>,       // each one a `Stmt::Function`
+        span: Span
+    },
+    Enum {
+        name: Token,
+        variants: Vec<(Token, Vec<Token>)>, // each name with its fields, `Empty` has none
+        span: Span
     },
     Block {
-        statements: Vec<Box<Stmt>>
+        statements: Vec<Stmt>,
+        span: Span
+    },
+    If {
+        condition: Expr,
+        then_branch: Box<Stmt>,
+        else_branch: Option<Box<Stmt>>,
+        span: Span
+    },
+    While {
+        label: Option<Token>,
+        condition: Expr,
+        body: Box<Stmt>,
+        span: Span
+    },
+    For {
+        label: Option<Token>,
+        initializer: Option<Box<Stmt>>, // a let or an expression statement
+        condition: Option<Box<Expr>>,   // none loops forever
+        increment: Option<Box<Expr>>,
+        body: Box<Stmt>,
+        span: Span
+    },
+    Return {
+        value: Option<Expr>, // a bare `return;` gives null
+        span: Span
+    },
+    Break {
+        label: Option<Token>,
+        span: Span
+    },
+    Continue {
+        label: Option<Token>,
+        span: Span
+    }
+}
+
+impl Stmt {
+
+    /// The part of the source the whole statement was parsed from.
+    pub fn span(&self) -> Span {
+        match self {
+            Stmt::Expression { span, .. }
+            | Stmt::Let { span, .. }
+            | Stmt::Function { span, .. }
+            | Stmt::Class { span, .. }
+            | Stmt::Enum { span, .. }
+            | Stmt::Block { span, .. }
+            | Stmt::If { span, .. }
+            | Stmt::While { span, .. }
+            | Stmt::For { span, .. }
+            | Stmt::Return { span, .. }
+            | Stmt::Break { span, .. }
+            | Stmt::Continue { span, .. } => *span,
+        }
+    }
+
+    pub fn execute(&self, environment: &Rc<RefCell<Environment>>) -> Result<Flow, RuntimeError> {
+        match self {
+            Stmt::Expression { expression, .. } => {
+                expression.evaluate(environment)?;
+            },
+            Stmt::Let { name, initializer, .. } => {
+                let value = match initializer {
+                    Some(initializer) => initializer.evaluate(environment)?,
+                    None => Value::Null,
+                };
+                environment.borrow_mut().define(&name.lexeme, value);
+            },
+            Stmt::Block { statements, .. } => {
+                let scope = Environment::with_enclosing(environment.clone());
+
+                for statement in statements {
+                    let flow = statement.execute(&scope)?;
+                    if flow != Flow::Normal {
+                        return Ok(flow);
+                    }
+                }
+            },
+            Stmt::If { condition, then_branch, else_branch, .. } => {
+                if condition.evaluate(environment)?.is_truthy() {
+                    return then_branch.execute(environment);
+                } else if let Some(else_branch) = else_branch {
+                    return else_branch.execute(environment);
+                }
+            },
+            Stmt::While { label, condition, body, .. } => {
+                while condition.evaluate(environment)?.is_truthy() {
+                    let flow = body.execute(environment)?;
+                    match flow {
+                        Flow::Break(ref target) if Self::targets(label, target) => break,
+                        Flow::Continue(ref target) if Self::targets(label, target) => (),
+                        Flow::Normal => (),
+                        _ => return Ok(flow),
+                    }
+                }
+            },
+            Stmt::For { label, initializer, condition, increment, body, .. } => {
+                // the initializer's variables only live as long as the loop
+                let mut scope = Environment::with_enclosing(environment.clone());
+
+                if let Some(initializer) = initializer {
+                    initializer.execute(&scope)?;
+                }
+
+                loop {
+                    if let Some(condition) = condition {
+                        if !condition.evaluate(&scope)?.is_truthy() {
+                            break;
+                        }
+                    }
+
+                    // a `continue` still runs the increment
+                    let flow = body.execute(&scope)?;
+                    match flow {
+                        Flow::Break(ref target) if Self::targets(label, target) => break,
+                        Flow::Continue(ref target) if Self::targets(label, target) => (),
+                        Flow::Normal => (),
+                        _ => return Ok(flow),
+                    }
+
+                    // every iteration gets its own copy of the loop variables, so closures made in
+                    // the body keep the values they saw instead of the last ones
+                    let next = scope.borrow().duplicate();
+                    scope = next;
+
+                    if let Some(increment) = increment {
+                        increment.evaluate(&scope)?;
+                    }
+                }
+            },
+            Stmt::Function { name, params, body, .. } => {
+                let function = Value::function(&name.lexeme, params, body, environment);
+                environment.borrow_mut().define(&name.lexeme, function);
+            },
+            Stmt::Class { name, superclass, methods, .. } => {
+                let superclass = match superclass {
+                    Some(superclass) => match environment.borrow().get(superclass)? {
+                        Value::Class(class) => Some(class),
+                        other => return Err(RuntimeError::at(RuntimeErrorKind::InvalidSuperclass(other.type_name()), superclass)),
+                    },
+                    None => None,
+                };
+
+                // methods of a subclass see its superclass as `super`
+                let closure = match &superclass {
+                    Some(class) => {
+                        let scope = Environment::with_enclosing(environment.clone());
+                        scope.borrow_mut().define("super", Value::Class(class.clone()));
+                        scope
+                    },
+                    None => environment.clone(),
+                };
+
+                let methods = methods.iter().filter_map(|method| match method {
+                    Stmt::Function { name, params, body, .. } => {
+                        Some((name.lexeme.clone(), Method { params: params.clone(), body: body.clone() }))
+                    },
+                    _ => None,
+                }).collect::<HashMap<_, _>>();
+
+                let class = Class { name: name.lexeme.clone(), superclass, methods, closure };
+                environment.borrow_mut().define(&name.lexeme, Value::Class(Rc::new(class)));
+            },
+            Stmt::Enum { name, variants, .. } => {
+                let variants = variants.iter().map(|(variant, fields)| Variant {
+                    name: variant.lexeme.clone(),
+                    fields: fields.iter().map(|field| field.lexeme.clone()).collect(),
+                }).collect();
+
+                let enumeration = Enum { name: name.lexeme.clone(), variants };
+                environment.borrow_mut().define(&name.lexeme, Value::Enum(Rc::new(enumeration)));
+            },
+            Stmt::Return { value, .. } => {
+                let value = match value {
+                    Some(value) => value.evaluate(environment)?,
+                    None => Value::Null,
+                };
+                return Ok(Flow::Return(value));
+            },
+            Stmt::Break { label, .. } => {
+                return Ok(Flow::Break(label.as_ref().map(|label| label.lexeme.clone())));
+            },
+            Stmt::Continue { label, .. } => {
+                return Ok(Flow::Continue(label.as_ref().map(|label| label.lexeme.clone())));
+            },
+        }
+        Ok(Flow::Normal)
+    }
+
+    // whether a `break` or `continue` aimed at `target` stops at the loop labelled `label`,
+    // the parser already checked that some enclosing loop has that label
+    fn targets(label: &Option<Token>, target: &Option<String>) -> bool {
+        match target {
+            None => true,
+            Some(target) => label.as_ref().is_some_and(|label| &label.lexeme == target),
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    use crate::{ast::{error::RuntimeErrorKind, value::MAX_CALL_DEPTH}, interpreter::interpreter::STACK_SIZE, lexer::{lexer::Lexer, tokens::{Token, TokenKind}}, parser::parser::Parser};
+
+    fn run(source: &str, environment: &Rc<RefCell<Environment>>) -> Result<(), RuntimeError> {
+        let tokens = Lexer::new("filename", source.to_string()).tokenize().unwrap();
+        let stmts = Parser::new(tokens).parse().ok().unwrap();
+
+        for stmt in stmts {
+            stmt.execute(environment)?;
+        }
+        Ok(())
+    }
+
+    fn identifier(name: &str) -> Token {
+        Token::dynamic_tokenkind("filename".to_string(), TokenKind::Identifier, name.to_string(), 1, 1).unwrap()
+    }
+
+    #[test]
+    fn let_and_read_variables() {
+        let environment = Environment::new();
+
+        run("let x = 1 + 2; let y = x * 2; let s = \"${x}-${y}\";", &environment).unwrap();
+
+        assert_eq!(environment.borrow().get(&identifier("y")), Ok(Value::Number(6.0)));
+        assert_eq!(environment.borrow().get(&identifier("s")), Ok(Value::String("3-6".to_string())));
+    }
+
+    #[test]
+    fn let_without_initializer_is_null() {
+        let environment = Environment::new();
+
+        run("let x;", &environment).unwrap();
+
+        assert_eq!(environment.borrow().get(&identifier("x")), Ok(Value::Null));
+    }
+
+    #[test]
+    fn undefined_variable_is_a_runtime_error() {
+        let environment = Environment::new();
+
+        let error = run("let x = y + 1;", &environment).unwrap_err();
+
+        assert_eq!(error.kind, RuntimeErrorKind::UndefinedVariable("y".to_string()));
+        assert_eq!(error.span, Span::new(8, 9));
+    }
+
+    #[test]
+    fn block_variables_end_with_the_block() {
+        let environment = Environment::new();
+
+        run("let x = 1; { let x = 2; let y = x; }", &environment).unwrap();
+
+        assert_eq!(environment.borrow().get(&identifier("x")), Ok(Value::Number(1.0)));
+        assert!(environment.borrow().get(&identifier("y")).is_err());
+    }
+
+    #[test]
+    fn block_reads_outer_variables() {
+        let environment = Environment::new();
+
+        run("let x = 1; let y; { let z = x + 1; { let w = z * 10; } }", &environment).unwrap();
+
+        assert_eq!(environment.borrow().get(&identifier("x")), Ok(Value::Number(1.0)));
+    }
+
+    #[test]
+    fn assignment_reaches_the_declaring_scope() {
+        let environment = Environment::new();
+
+        run("let x = 1; let y = 0; { let y = 5; x = y = 7; x += 3; x %= 4; }", &environment).unwrap();
+
+        assert_eq!(environment.borrow().get(&identifier("x")), Ok(Value::Number(2.0)));
+        assert_eq!(environment.borrow().get(&identifier("y")), Ok(Value::Number(0.0)));
+    }
+
+    #[test]
+    fn compound_assignment_on_strings() {
+        let environment = Environment::new();
+
+        run("let s = \"a\"; s += \"b\";", &environment).unwrap();
+
+        assert_eq!(environment.borrow().get(&identifier("s")), Ok(Value::String("ab".to_string())));
+    }
+
+    #[test]
+    fn assignment_to_undeclared_variable() {
+        let environment = Environment::new();
+
+        let error = run("let a = 1;\nb = a;", &environment).unwrap_err();
+
+        assert_eq!(error.kind, RuntimeErrorKind::UndefinedVariable("b".to_string()));
+        assert_eq!(error.span, Span::new(11, 12));
+    }
+
+    #[test]
+    fn assignment_evaluates_the_target_first() {
+        let environment = Environment::new();
+
+        run("let order = \"\";
+            fn step(name, value) { order = \"${order}${name}\"; return value; }
+            let a = [10, [1, 2]];
+            a[step(\"i\", 0)] += step(\"f\", 1);
+            a[step(\"j\", 1)][step(\"k\", 0)] = step(\"g\", 5);", &environment).unwrap();
+
+        assert_eq!(environment.borrow().get(&identifier("order")), Ok(Value::String("ifjkg".to_string())));
+        assert_eq!(environment.borrow().get(&identifier("a")).unwrap().to_string(), "[11, [5, 2]]");
+    }
+
+    #[test]
+    fn assignment_keeps_changes_the_value_makes() {
+        let environment = Environment::new();
+
+        run("let a = [1, 2]; fn g() { a[1] = 99; return 0; } a[0] = g();
+            let o = { n: 1, m: 1 }; fn h() { o.m = 5; return 2; } o.n += h();
+            let n = o.n; let m = o.m;", &environment).unwrap();
+
+        assert_eq!(environment.borrow().get(&identifier("a")).unwrap().to_string(), "[0, 99]");
+        assert_eq!(environment.borrow().get(&identifier("n")), Ok(Value::Number(3.0)));
+        assert_eq!(environment.borrow().get(&identifier("m")), Ok(Value::Number(5.0)));
+    }
+
+    #[test]
+    fn compound_assignment_type_error() {
+        let environment = Environment::new();
+
+        let error = run("let a = true; a -= 1;", &environment).unwrap_err();
+
+        assert_eq!(error.message(), "Binary operator '-=' is not supported between Boolean and Number");
+    }
+
+    #[test]
+    fn if_else_chain() {
+        let environment = Environment::new();
+
+        run("let n = 15; let kind;
+            if (n % 2 == 0) kind = \"even\"; else if (n > 10) { kind = \"big odd\"; } else kind = \"odd\";", &environment).unwrap();
+
+        assert_eq!(environment.borrow().get(&identifier("kind")), Ok(Value::String("big odd".to_string())));
+    }
+
+    #[test]
+    fn if_uses_truthiness() {
+        let environment = Environment::new();
+
+        run("let a = 0; let b = 0; if (\"\") a = 1; if (\"text\") b = 1;", &environment).unwrap();
+
+        assert_eq!(environment.borrow().get(&identifier("a")), Ok(Value::Number(0.0)));
+        assert_eq!(environment.borrow().get(&identifier("b")), Ok(Value::Number(1.0)));
+    }
+
+    #[test]
+    fn logical_operators_use_truthiness() {
+        let environment = Environment::new();
+
+        run("let a = 1; let b = \"x\"; let both = 0; if (a and b) both = 1;
+            let first = 0 or \"default\"; let kept = \"set\" or boom();
+            let empty = [] and boom(); let last = 1 and [2];", &environment).unwrap();
+
+        let get = |name| environment.borrow().get(&identifier(name)).unwrap();
+        assert_eq!(get("both"), Value::Number(1.0));
+        assert_eq!(get("first"), Value::String("default".to_string()));
+        assert_eq!(get("kept"), Value::String("set".to_string()));
+        assert_eq!(get("empty"), Value::Array(vec![]));
+        assert_eq!(get("last"), Value::Array(vec![Value::Number(2.0)]));
+    }
+
+    #[test]
+    fn while_loop() {
+        let environment = Environment::new();
+
+        run("let i = 0; let sum = 0; while (i < 5) { i += 1; sum += i; }", &environment).unwrap();
+
+        assert_eq!(environment.borrow().get(&identifier("sum")), Ok(Value::Number(15.0)));
+    }
+
+    #[test]
+    fn for_loop_scopes_its_initializer() {
+        let environment = Environment::new();
+
+        run("let total = 1; for (let i = 0; i < 4; i += 1) total *= 2;", &environment).unwrap();
+
+        assert_eq!(environment.borrow().get(&identifier("total")), Ok(Value::Number(16.0)));
+        assert!(environment.borrow().get(&identifier("i")).is_err());
+    }
+
+    #[test]
+    fn for_loop_with_outer_variable() {
+        let environment = Environment::new();
+
+        run("let i = 10; for (i = 0; i < 3;) i += 1;", &environment).unwrap();
+
+        assert_eq!(environment.borrow().get(&identifier("i")), Ok(Value::Number(3.0)));
+    }
+
+    #[test]
+    fn break_and_continue() {
+        let environment = Environment::new();
+
+        run("let sum = 0;
+            for (let i = 0; i < 10; i += 1) {
+                if (i % 2 == 0) continue;
+                if (i > 7) break;
+                sum += i;
+            }
+            let n = 0;
+            while (true) { n += 1; if (n == 3) break; }", &environment).unwrap();
+
+        assert_eq!(environment.borrow().get(&identifier("sum")), Ok(Value::Number(16.0)));
+        assert_eq!(environment.borrow().get(&identifier("n")), Ok(Value::Number(3.0)));
+    }
+
+    #[test]
+    fn labelled_break_and_continue() {
+        let environment = Environment::new();
+
+        run("let pairs = 0; let found = \"\";
+            outer: for (let i = 0; i < 5; i += 1) {
+                let j = 0;
+                while (j < 5) {
+                    j += 1;
+                    if (j > i) continue outer;
+                    pairs += 1;
+                    if (i * j == 6) { found = \"${i}x${j}\"; break outer; }
+                }
+            }", &environment).unwrap();
+
+        assert_eq!(environment.borrow().get(&identifier("pairs")), Ok(Value::Number(5.0)));
+        assert_eq!(environment.borrow().get(&identifier("found")), Ok(Value::String("3x2".to_string())));
+    }
+
+    #[test]
+    fn function_call_with_return() {
+        let environment = Environment::new();
+
+        run("fn add(a, b) { return a + b; } let sum = add(1, 2);
+            fn nothing() { return; } let empty = nothing();
+            fn no_return() { 1; } let also_empty = no_return();", &environment).unwrap();
+
+        assert_eq!(environment.borrow().get(&identifier("sum")), Ok(Value::Number(3.0)));
+        assert_eq!(environment.borrow().get(&identifier("empty")), Ok(Value::Null));
+        assert_eq!(environment.borrow().get(&identifier("also_empty")), Ok(Value::Null));
+    }
+
+    #[test]
+    fn return_from_inside_a_loop() {
+        let environment = Environment::new();
+
+        run("fn first_over(limit) { for (let i = 0; ; i += 1) { while (true) { if (i * i > limit) return i; break; } } }
+            let n = first_over(50);", &environment).unwrap();
+
+        assert_eq!(environment.borrow().get(&identifier("n")), Ok(Value::Number(8.0)));
+    }
+
+    #[test]
+    fn recursion() {
+        let environment = Environment::new();
+
+        run("fn fib(n) { if (n < 2) return n; return fib(n - 1) + fib(n - 2); } let f = fib(15);", &environment).unwrap();
+
+        assert_eq!(environment.borrow().get(&identifier("f")), Ok(Value::Number(610.0)));
+    }
+
+    #[test]
+    fn deep_recursion_is_an_error() {
+        // a thread with a stack like the one `main` runs scripts on, the test threads have less
+        let (deep, too_deep) = thread::Builder::new().stack_size(STACK_SIZE).spawn(|| {
+            let environment = Environment::new();
+            let source = "fn c(n) { if (n == 0) return 0; return c(n - 1); }\n";
+
+            let deep = run(&format!("{source}let d = c({});", MAX_CALL_DEPTH - 1), &environment);
+            let too_deep = run(&format!("{source}c({});", MAX_CALL_DEPTH), &environment).unwrap_err();
+            (deep, too_deep)
+        }).unwrap().join().unwrap();
+
+        assert_eq!(deep, Ok(()));
+        assert_eq!(too_deep.kind, RuntimeErrorKind::StackOverflow(MAX_CALL_DEPTH));
+        assert_eq!((too_deep.line_number, too_deep.column_number), (1, 48));
+        assert!(too_deep.help.is_some());
+    }
+
+    #[test]
+    fn closures_keep_their_environment() {
+        let environment = Environment::new();
+
+        run("fn counter() { let count = 0; fn next() { count += 1; return count; } return next; }
+            let a = counter(); let b = counter();
+            a(); a(); b();
+            let from_a = a(); let from_b = b();", &environment).unwrap();
+
+        assert_eq!(environment.borrow().get(&identifier("from_a")), Ok(Value::Number(3.0)));
+        assert_eq!(environment.borrow().get(&identifier("from_b")), Ok(Value::Number(2.0)));
+    }
+
+    #[test]
+    fn closures_capture_each_loop_iteration() {
+        let environment = Environment::new();
+
+        run("let first; let second;
+            for (let i = 0; i < 2; i += 1) {
+                fn get() { return i; }
+                if (i == 0) first = get; else second = get;
+            }
+            let a = first(); let b = second();", &environment).unwrap();
+
+        assert_eq!(environment.borrow().get(&identifier("a")), Ok(Value::Number(0.0)));
+        assert_eq!(environment.borrow().get(&identifier("b")), Ok(Value::Number(1.0)));
+    }
+
+    #[test]
+    fn arity_mismatch() {
+        let environment = Environment::new();
+
+        let error = run("fn one(a) { return a; } one(1, 2);", &environment).unwrap_err();
+
+        assert_eq!(error.message(), "Callable one expected 1 arguments but got 2");
+    }
+
+    #[test]
+    fn function_expressions_as_callbacks() {
+        let environment = Environment::new();
+
+        run("fn sort2(a, b, before) { if (before(b, a)) return \"${b} ${a}\"; return \"${a} ${b}\"; }
+            let ascending = sort2(2, 1, (x, y) => x < y);
+            let descending = sort2(1, 2, fn (x, y) { return x > y; });
+            fn twice(f, x) { return f(f(x)); }
+            let offset = 10;
+            let moved = twice((x) => x + offset, 1);", &environment).unwrap();
+
+        assert_eq!(environment.borrow().get(&identifier("ascending")), Ok(Value::String("1 2".to_string())));
+        assert_eq!(environment.borrow().get(&identifier("descending")), Ok(Value::String("2 1".to_string())));
+        assert_eq!(environment.borrow().get(&identifier("moved")), Ok(Value::Number(21.0)));
+    }
+
+    #[test]
+    fn anonymous_function_display() {
+        let environment = Environment::new();
+
+        run("let f = () => null;", &environment).unwrap();
+
+        assert_eq!(environment.borrow().get(&identifier("f")).unwrap().to_string(), "<fn anonymous>");
+    }
+
+    #[test]
+    fn functions_are_only_equal_to_themselves() {
+        let environment = Environment::new();
+
+        run("let f = fn (a) { return a; };
+            let g = fn (a) { return a; };
+            let same = f == f;
+            let copied = f;
+            let alias = copied == f;
+            let different = f == g;", &environment).unwrap();
+
+        assert_eq!(environment.borrow().get(&identifier("same")), Ok(Value::Boolean(true)));
+        assert_eq!(environment.borrow().get(&identifier("alias")), Ok(Value::Boolean(true)));
+        assert_eq!(environment.borrow().get(&identifier("different")), Ok(Value::Boolean(false)));
+    }
+
+    #[test]
+    fn null_equality_and_coalescing() {
+        let environment = Environment::new();
+
+        run("let missing;
+            let same = missing == null;
+            let different = null != 0 and null != false and \"1\" != 1;
+            let fallback = missing ?? \"default\";
+            let kept = 0 ?? boom();", &environment).unwrap();
+
+        assert_eq!(environment.borrow().get(&identifier("same")), Ok(Value::Boolean(true)));
+        assert_eq!(environment.borrow().get(&identifier("different")), Ok(Value::Boolean(true)));
+        assert_eq!(environment.borrow().get(&identifier("fallback")), Ok(Value::String("default".to_string())));
+        assert_eq!(environment.borrow().get(&identifier("kept")), Ok(Value::Number(0.0)));
+    }
+
+    #[test]
+    fn optional_chaining_on_null() {
+        let environment = Environment::new();
+
+        run("let user; let on_done;
+            let city = user?.address?.city ?? \"unknown\";
+            let result = on_done?.(boom());", &environment).unwrap();
+
+        assert_eq!(environment.borrow().get(&identifier("city")), Ok(Value::String("unknown".to_string())));
+        assert_eq!(environment.borrow().get(&identifier("result")), Ok(Value::Null));
+    }
+
+    #[test]
+    fn optional_chaining_skips_the_rest_of_the_chain() {
+        let environment = Environment::new();
+
+        run("let u = null; let counter = { n: 1, label: \"n\" };
+            let city = u?.address.city;
+            let called = u?.f();
+            let indexed = u?.items[0];
+            let grouped = (u?.address) ?? \"none\";
+            let kept = counter?.label;", &environment).unwrap();
+
+        let get = |name| environment.borrow().get(&identifier(name)).unwrap();
+        assert_eq!(get("city"), Value::Null);
+        assert_eq!(get("called"), Value::Null);
+        assert_eq!(get("indexed"), Value::Null);
+        assert_eq!(get("grouped"), Value::String("none".to_string()));
+        assert_eq!(get("kept"), Value::String("n".to_string()));
+
+        // parentheses end the chain, so the property after them is read from null
+        let error = run("(u?.address).city;", &environment).unwrap_err();
+        assert_eq!(error.message(), "Null has no properties");
+    }
+
+    #[test]
+    fn optional_method_call() {
+        let environment = Environment::new();
+
+        run("class Greeter { fn hi() { return \"hi\"; } }
+            let g = Greeter(); let none = null;
+            let greeting = g?.hi(); let skipped = none?.hi();", &environment).unwrap();
+
+        assert_eq!(environment.borrow().get(&identifier("greeting")), Ok(Value::String("hi".to_string())));
+        assert_eq!(environment.borrow().get(&identifier("skipped")), Ok(Value::Null));
+    }
+
+    #[test]
+    fn property_of_a_non_object() {
+        let environment = Environment::new();
+
+        let error = run("let user; user.name;", &environment).unwrap_err();
+
+        assert_eq!(error.message(), "Null has no properties");
+        assert_eq!(error.span, Span::new(15, 19));
+    }
+
+    #[test]
+    fn array_indexing_and_slicing() {
+        let environment = Environment::new();
+
+        run("let a = [10, 20, 30, 40];
+            let first = a[0]; let last = a[-1];
+            let middle = a[1:3]; let tail = a[-2:]; let head = a[:1]; let none = a[3:1]; let clamped = a[-10:10];", &environment).unwrap();
+
+        let get = |name| environment.borrow().get(&identifier(name)).unwrap().to_string();
+        assert_eq!(get("first"), "10");
+        assert_eq!(get("last"), "40");
+        assert_eq!(get("middle"), "[20, 30]");
+        assert_eq!(get("tail"), "[30, 40]");
+        assert_eq!(get("head"), "[10]");
+        assert_eq!(get("none"), "[]");
+        assert_eq!(get("clamped"), "[10, 20, 30, 40]");
+    }
+
+    #[test]
+    fn array_element_assignment() {
+        let environment = Environment::new();
+
+        run("let grid = [[1, 2], [3, 4]]; let copy = grid;
+            grid[0][1] = 5; grid[-1][0] += 10;
+            let calls = 0; fn next() { calls += 1; return 1; }
+            grid[next()][next()] *= 2;", &environment).unwrap();
+
+        let get = |name| environment.borrow().get(&identifier(name)).unwrap().to_string();
+        assert_eq!(get("grid"), "[[1, 5], [13, 8]]");
+        assert_eq!(get("copy"), "[[1, 2], [3, 4]]");
+        assert_eq!(get("calls"), "2");
+    }
+
+    #[test]
+    fn index_out_of_bounds() {
+        let environment = Environment::new();
+
+        let error = run("let a = [1, 2, 3]; a[-4];", &environment).unwrap_err();
+
+        assert_eq!(error.message(), "Index -4 is out of bounds for length 3");
+        assert_eq!(error.span, Span::new(21, 23));
+
+        let error = run("a[3] = 1;", &environment).unwrap_err();
+        assert_eq!(error.kind, RuntimeErrorKind::IndexOutOfBounds { index: 3, length: 3 });
+    }
+
+    #[test]
+    fn invalid_indexes() {
+        let environment = Environment::new();
+
+        run("let a = [1]; let n = 1;", &environment).unwrap();
+
+        assert_eq!(run("a[0.5];", &environment).unwrap_err().message(), "Index must be a whole number, found 0.5");
+        assert_eq!(run("a[\"0\"];", &environment).unwrap_err().message(), "Index must be a whole number, found String");
+        assert_eq!(run("n[0];", &environment).unwrap_err().message(), "Number cannot be indexed");
+    }
+
+    #[test]
+    fn object_literals_and_fields() {
+        let environment = Environment::new();
+
+        run("let name = \"x\"; let k = \"dyn\";
+            let o = { name, \"quoted key\": 1, [k + \"amic\"]: 2, nested: { n: [1, 2] } };
+            o.quoted = o[\"quoted key\"] + 1;
+            o[\"dynamic\"] *= 10;
+            o.nested.n[0] = \"first\";
+            o.extra = o.name + \"!\";
+            let a = o.name; let b = o.quoted; let c = o.dynamic; let d = o.nested.n; let e = o[\"name\"];", &environment).unwrap();
+
+        let get = |name| environment.borrow().get(&identifier(name)).unwrap().to_string();
+        assert_eq!(get("a"), "x");
+        assert_eq!(get("b"), "2");
+        assert_eq!(get("c"), "20");
+        assert_eq!(get("d"), "[first, 2]");
+        assert_eq!(get("e"), "x");
+        assert_eq!(get("o"), "{dynamic: 20, extra: x!, name: x, nested: {n: [first, 2]}, quoted: 2, quoted key: 1}");
+    }
+
+    #[test]
+    fn object_errors() {
+        let environment = Environment::new();
+
+        run("let o = { a: 1 };", &environment).unwrap();
+
+        assert_eq!(run("o[\"b\"];", &environment).unwrap_err().message(), "Undefined property 'b'");
+        assert_eq!(run("o[1];", &environment).unwrap_err().message(), "Object keys must be strings, found Number");
+        assert_eq!(run("let p = { [true]: 1 };", &environment).unwrap_err().message(), "Object keys must be strings, found Boolean");
+        assert_eq!(run("o.a.b = 2;", &environment).unwrap_err().message(), "Number has no properties");
+    }
+
+    #[test]
+    fn conditional_is_lazy() {
+        let environment = Environment::new();
+
+        run("fn sign(n) { return n > 0 ? \"positive\" : n < 0 ? \"negative\" : \"zero\"; }
+            let a = sign(3); let b = sign(-1); let c = sign(0);
+            let safe = true ? 1 : boom();", &environment).unwrap();
+
+        let get = |name| environment.borrow().get(&identifier(name)).unwrap().to_string();
+        assert_eq!(get("a"), "positive");
+        assert_eq!(get("b"), "negative");
+        assert_eq!(get("c"), "zero");
+        assert_eq!(get("safe"), "1");
+    }
+
+    #[test]
+    fn classes_and_instances() {
+        let environment = Environment::new();
+
+        run("class Point {
+                fn init(x, y) { self.x = x; self.y = y; }
+                fn len() { return self.x * self.x + self.y * self.y; }
+                fn moved(dx) { self.x += dx; return self; }
+            }
+            let p = Point(3, 4);
+            let len = p.len();
+            let bound = p.len;
+            let q = p;
+            q.moved(1).y = 0;
+            let after = bound();
+            let shown = \"${Point} ${p}\";
+            let same = p == q; let other = p == Point(4, 0);", &environment).unwrap();
+
+        let get = |name| environment.borrow().get(&identifier(name)).unwrap().to_string();
+        assert_eq!(get("len"), "25");
+        assert_eq!(get("after"), "16"); // instances are shared, `bound` still sees the same `p`
+        assert_eq!(get("shown"), "<class Point> <Point instance>");
+        assert_eq!(get("same"), "true");
+        assert_eq!(get("other"), "false");
+    }
+
+    #[test]
+    fn class_errors() {
+        let environment = Environment::new();
+
+        run("class Empty {} class Pair { fn init(a, b) {} } let e = Empty();", &environment).unwrap();
+
+        assert_eq!(run("Pair(1);", &environment).unwrap_err().message(), "Callable Pair expected 2 arguments but got 1");
+        assert_eq!(run("e.missing;", &environment).unwrap_err().message(), "Undefined property 'missing'");
+        assert_eq!(run("let m = e?.missing;", &environment), Ok(()));
+        assert_eq!(run("e();", &environment).unwrap_err().message(), "Instance is not callable");
+    }
+
+    #[test]
+    fn inheritance_and_super() {
+        let environment = Environment::new();
+
+        run("class Shape {
+                fn init(name) { self.name = name; }
+                fn describe() { return \"${self.name} with area ${self.area()}\"; }
+                fn area() { return 0; }
+            }
+            class Square < Shape {
+                fn init(side) { super.init(\"square\"); self.side = side; }
+                fn area() { return self.side * self.side; }
+            }
+            class Tile : Square {
+                fn describe() { return \"tile, \" + super.describe(); }
+            }
+            let a = Shape(\"dot\").describe();
+            let b = Square(2).describe();
+            let c = Tile(3).describe();", &environment).unwrap();
+
+        let get = |name| environment.borrow().get(&identifier(name)).unwrap().to_string();
+        assert_eq!(get("a"), "dot with area 0");
+        assert_eq!(get("b"), "square with area 4");
+        assert_eq!(get("c"), "tile, square with area 9");
+    }
+
+    #[test]
+    fn inheritance_errors() {
+        let environment = Environment::new();
+
+        let error = run("let A = 1; class B < A {}", &environment).unwrap_err();
+        assert_eq!(error.message(), "Superclass must be a class, found Number");
+        assert_eq!(error.code(), "R0011");
+
+        assert_eq!(run("class C < Missing {}", &environment).unwrap_err().message(), "Undefined variable 'Missing'");
+        assert_eq!(
+            run("class D {} class E < D { fn f() { return super.f(); } } E().f();", &environment).unwrap_err().message(),
+            "Undefined property 'f'"
+        );
+    }
+
+    #[test]
+    fn enums_and_variants() {
+        let environment = Environment::new();
+
+        run("enum Shape { Circle(r), Rect(w, h), Empty }
+            let circle = Shape.Circle(2);
+            let rect = Shape.Rect(1, 3);
+            let shown = \"${circle} ${rect} ${Shape.Empty} ${Shape}\";
+            let radius = circle.r;
+            let same = circle == Shape.Circle(2);
+            let different = circle == Shape.Circle(3) or Shape.Empty == Shape.Rect(1, 3);
+            enum Other { Empty }
+            let other = Shape.Empty == Other.Empty;", &environment).unwrap();
+
+        let get = |name| environment.borrow().get(&identifier(name)).unwrap().to_string();
+        assert_eq!(get("shown"), "Shape.Circle(2) Shape.Rect(1, 3) Shape.Empty <enum Shape>");
+        assert_eq!(get("radius"), "2");
+        assert_eq!(get("same"), "true");
+        assert_eq!(get("different"), "false");
+        assert_eq!(get("other"), "false");
+    }
+
+    #[test]
+    fn enum_errors() {
+        let environment = Environment::new();
+
+        run("enum Shape { Circle(r), Empty }", &environment).unwrap();
+
+        let error = run("Shape.Square;", &environment).unwrap_err();
+        assert_eq!(error.message(), "Undefined property 'Square'");
+        assert_eq!(error.help, Some("Shape has the variants Circle, Empty".to_string()));
+        assert_eq!(run("Shape.Circle();", &environment).unwrap_err().message(), "Callable Shape.Circle expected 1 arguments but got 0");
+        assert_eq!(run("Shape.Empty();", &environment).unwrap_err().message(), "Variant is not callable");
+        assert_eq!(run("Shape.Circle(1).w;", &environment).unwrap_err().message(), "Undefined property 'w'");
+    }
+
+    #[test]
+    fn match_expressions() {
+        let environment = Environment::new();
+
+        run("enum Shape { Circle(r), Rect(w, h), Empty }
+            fn describe(value) {
+                return match value {
+                    0 => \"zero\",
+                    -1 => \"minus one\",
+                    null => \"nothing\",
+                    [a, b] => \"pair ${a} ${b}\",
+                    [_, _, _] => \"triple\",
+                    { name, age: 1 } => \"baby ${name}\",
+                    { name } => \"named ${name}\",
+                    Shape.Circle(r) if r > 1 => \"big circle ${r}\",
+                    Shape.Circle(_) => \"small circle\",
+                    Shape.Rect(w, w2) => \"rect ${w * w2}\",
+                    Shape.Empty => \"empty\",
+                    _ => \"something else\",
+                };
+            }
+            let a = describe(0); let b = describe(-1); let c = describe([1, 2]); let d = describe([1, 2, 3]);
+            let e = describe({ name: \"x\", age: 1 }); let f = describe({ name: \"y\", age: 2 });
+            let g = describe(Shape.Circle(2)); let h = describe(Shape.Circle(1)); let i = describe(Shape.Rect(2, 3));
+            let j = describe(Shape.Empty); let k = describe(true);", &environment).unwrap();
+
+        let get = |name| environment.borrow().get(&identifier(name)).unwrap().to_string();
+        assert_eq!(get("a"), "zero");
+        assert_eq!(get("b"), "minus one");
+        assert_eq!(get("c"), "pair 1 2");
+        assert_eq!(get("d"), "triple");
+        assert_eq!(get("e"), "baby x");
+        assert_eq!(get("f"), "named y");
+        assert_eq!(get("g"), "big circle 2");
+        assert_eq!(get("h"), "small circle");
+        assert_eq!(get("i"), "rect 6");
+        assert_eq!(get("j"), "empty");
+        assert_eq!(get("k"), "something else");
+    }
+
+    #[test]
+    fn match_bindings_are_scoped() {
+        let environment = Environment::new();
+
+        run("let x = 1; let y = match [5] { [x] => x + 1 }; let after = x;", &environment).unwrap();
+
+        let get = |name| environment.borrow().get(&identifier(name)).unwrap().to_string();
+        assert_eq!(get("y"), "6");
+        assert_eq!(get("after"), "1");
+    }
+
+    #[test]
+    fn match_without_matching_arm() {
+        let environment = Environment::new();
+
+        let error = run("let s = \"text\"; match s { \"other\" => 1 };", &environment).unwrap_err();
+        assert_eq!(error.message(), "No match arm matches \"text\"");
+        assert_eq!(error.code(), "R0012");
+        assert_eq!(error.help, Some("add a `_ => ...` arm to handle every other value".to_string()));
+
+        assert_eq!(run("match [1, 2] { [a] => a };", &environment).unwrap_err().message(), "No match arm matches [1, 2]");
     }
-}
\ No newline at end of file
+}